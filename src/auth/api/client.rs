@@ -1,120 +1,323 @@
 use crate::api::types::HealthCheckResponse;
 use crate::api::{
-    Api, ApiError, ApiErrorResponse, IntoApi, OAuthErrorResponse, SignInUpBody, SignUpResponse,
+    AdminUserAttributes, AnonymousSignInBody, Api, ApiError, ApiErrorCode, ApiErrorResponse,
+    AuthorizeUrlResponse, ClientBuildError, CreateSsoProviderBody, DynTransport,
+    GenerateLinkRequest, GenerateLinkResponse, GotrueMetaSecurity, IdTokenCredentials, IntoApi,
+    InviteUserRequest, ListUsersQuery, OAuthErrorResponse, ResendBody, ResendRequest, Retry,
+    RetryPolicy, Settings, SignInUpBody, SignUpOptions, SignUpResponse, SsoBody, SsoProvider,
+    SsoProviderAttributes, SsoProviderList, TokenErrorResponse,
 };
-use crate::{EmailOrPhone, OAuthRequest, Session, User, UserList};
-use axum::http::{HeaderMap, HeaderValue, Method};
+use crate::{
+    AuthTypes, DefaultAuthTypes, EmailOrPhone, LogoutScope, OAuthRequest, Session, SsoRequest,
+    SsoTarget, User, UserAttributes, UserList,
+};
+use axum::body::Bytes;
+use axum::http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode};
 use bon::bon;
 use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
-use reqwest::{Client, Response};
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::Serialize;
 use serde_json::json;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{instrument, trace, warn};
+use std::time::{Duration, Instant};
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+use tracing::{debug, instrument, trace, warn};
 use url::Url;
+use uuid::Uuid;
 
-#[derive(Clone)]
-pub struct ApiClient {
+pub struct ApiClient<T: AuthTypes = DefaultAuthTypes> {
     url: Url,
-    client: Client,
+    transport: DynTransport,
     headers: Arc<HeaderMap>,
+    retry_policy: RetryPolicy,
+    _types: PhantomData<fn() -> T>,
+}
+
+impl<T: AuthTypes> Clone for ApiClient<T> {
+    fn clone(&self) -> Self {
+        Self {
+            url: self.url.clone(),
+            transport: self.transport.clone(),
+            headers: self.headers.clone(),
+            retry_policy: self.retry_policy.clone(),
+            _types: PhantomData,
+        }
+    }
 }
 
 #[bon]
-impl ApiClient {
+impl<T: AuthTypes> ApiClient<T> {
     #[instrument(
         name = "api_request",
         skip(self, body, access_token, query),
         fields(status)
     )]
     #[builder(finish_fn=send)]
-    async fn send_request<T, B, E>(
+    async fn send_request<R, B, E>(
         &self,
         #[builder(start_fn)] method: Method,
         #[builder(start_fn)] endpoint: &str,
         query: Option<&[(&str, &str)]>,
         body: Option<&B>,
         access_token: Option<&str>,
-    ) -> Result<T, ApiError>
+        #[builder(default)] retry: Retry,
+    ) -> Result<R, ApiError>
     where
-        T: DeserializeOwned,
+        R: DeserializeOwned,
         B: Serialize + ?Sized,
         E: DeserializeOwned + IntoApi + Debug,
     {
-        let url = self.url.join(endpoint)?;
+        let request = self.request(
+            method,
+            endpoint,
+            query.unwrap_or_default(),
+            body,
+            access_token,
+        )?;
+        let response = self.execute::<E>(endpoint, request, retry).await?;
 
-        let mut request = self
-            .client
-            .request(method, url)
-            .headers((*self.headers).clone());
+        Ok(serde_json::from_slice(response.body())?)
+    }
 
-        if let Some(q) = query {
-            if !q.is_empty() {
-                request = request.query(q);
-            }
+    /// Builds a request to `endpoint` with the configured headers and a JSON `body`.
+    fn request<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        body: Option<&B>,
+        access_token: Option<&str>,
+    ) -> Result<Request<Bytes>, ApiError> {
+        let mut url = self.url.join(endpoint)?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
         }
 
-        if let Some(b) = body {
-            request = request.json(b);
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        if let Some(headers) = request.headers_mut() {
+            headers.extend((*self.headers).clone());
         }
 
         if let Some(token) = access_token {
-            request = request.bearer_auth(token);
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        let body = match body {
+            Some(b) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Bytes::from(serde_json::to_vec(b)?)
+            }
+            None => Bytes::new(),
+        };
+
+        Ok(request.body(body)?)
+    }
+
+    /// Sends `request` to `endpoint` and returns the successful response.
+    async fn execute<E>(
+        &self,
+        endpoint: &str,
+        request: Request<Bytes>,
+        retry: Retry,
+    ) -> Result<Response<Bytes>, ApiError>
+    where
+        E: DeserializeOwned + IntoApi + Debug,
+    {
+        let started = Instant::now();
+        let result = self.send_with_retry::<E>(&request, retry, started).await;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_api_call(request.method(), endpoint, started, &result);
+        #[cfg(not(feature = "metrics"))]
+        let _ = endpoint;
+
+        result
+    }
+
+    /// Sends `request`, retrying transient errors as allowed by `retry`.
+    async fn send_with_retry<E>(
+        &self,
+        request: &Request<Bytes>,
+        retry: Retry,
+        started: Instant,
+    ) -> Result<Response<Bytes>, ApiError>
+    where
+        E: DeserializeOwned + IntoApi + Debug,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let result = match self.transport.send(clone_request(request)).await {
+                Ok(response) => handle_response::<E>(response),
+                Err(e) => Err(e.into()),
+            };
+
+            let error = match result {
+                Err(error) if attempt < self.retry_policy.max_retries && error.is_transient() => {
+                    error
+                }
+                result => return result,
+            };
+
+            let delay = match error {
+                ApiError::RateLimited {
+                    retry_after: Some(retry_after),
+                    ..
+                } => retry_after,
+                _ => self.retry_policy.backoff(attempt),
+            };
+            let retry = match retry {
+                Retry::Never => false,
+                Retry::Idempotent => delay <= self.retry_policy.max_backoff,
+                Retry::Within(interval) => {
+                    delay <= self.retry_policy.max_backoff && started.elapsed() + delay < interval
+                }
+            };
+            if !retry {
+                return Err(error);
+            }
+
+            debug!(%error, attempt, ?delay, "Retrying request");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
+    }
+}
 
-        request.send().await?.handle_response::<_, E>().await
+/// Request timeout if no transport is passed to [`ApiClient::builder`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_USER_AGENT: &str = "portal";
+
+#[bon]
+impl<T: AuthTypes> ApiClient<T> {
+    /// Creates a client, returning an error instead of panicking on an invalid API key.
+    ///
+    /// A passed `transport` is used as is, e.g. a `reqwest::Client` with a proxy or custom root
+    /// certificates. `timeout` and `user_agent` only apply to the default `reqwest` client,
+    /// without the `reqwest` feature a transport is required.
+    #[builder(
+        start_fn(name = builder, vis = "pub"),
+        builder_type(vis = "pub"),
+        finish_fn(name = build, vis = "pub")
+    )]
+    fn from_builder(
+        url: Url,
+        #[builder(into)] api_key: String,
+        #[builder(into)] transport: Option<DynTransport>,
+        #[builder(default = DEFAULT_TIMEOUT)] timeout: Duration,
+        #[builder(into, default = DEFAULT_USER_AGENT)] user_agent: String,
+        /// Sent with every request, e.g. `X-Client-Info`.
+        #[builder(default)]
+        headers: HeaderMap,
+        #[builder(default)] retry_policy: RetryPolicy,
+    ) -> Result<Self, ClientBuildError> {
+        let transport = match transport {
+            Some(transport) => transport,
+            #[cfg(feature = "reqwest")]
+            None => DynTransport::new(
+                reqwest::Client::builder()
+                    .timeout(timeout)
+                    .user_agent(user_agent)
+                    .build()?,
+            ),
+            #[cfg(not(feature = "reqwest"))]
+            None => {
+                let _ = (timeout, user_agent);
+                return Err(ClientBuildError::MissingTransport);
+            }
+        };
+
+        let mut headers = headers;
+        headers.insert("apiKey", HeaderValue::from_str(&api_key)?);
+
+        Ok(Self {
+            url,
+            transport,
+            headers: Arc::new(headers),
+            retry_policy,
+            _types: PhantomData,
+        })
     }
 }
 
-impl ApiClient {
+impl<T: AuthTypes> ApiClient<T> {
+    /// Creates a client with the default configuration.
+    ///
+    /// # Panics
+    ///
+    /// If `api_key` is not a valid header value. Use [`ApiClient::builder`] to handle the error.
+    #[cfg(feature = "reqwest")]
     pub fn new(url: Url, timeout: Duration, api_key: &str) -> Self {
-        let client = Client::builder()
+        Self::builder()
+            .url(url)
+            .api_key(api_key)
             .timeout(timeout)
-            .user_agent("portal")
             .build()
-            .unwrap();
+            .expect("invalid API client configuration")
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("apiKey", HeaderValue::from_str(api_key).unwrap());
-        let headers = Arc::new(headers);
+    /// Sets the retry policy of idempotent requests, defaults to [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
-        Self {
-            url,
-            client,
-            headers,
+    fn pkce_oauth_query<'a>(
+        req: &'a OAuthRequest,
+        challenge: &'a PkceCodeChallenge,
+    ) -> Vec<(&'static str, &'a str)> {
+        let mut query = vec![("provider", req.provider.as_str())];
+        if let Some(redirect_to) = &req.redirect_to {
+            query.push(("redirect_to", redirect_to));
         }
+        query.push(("code_challenge", challenge.as_str()));
+        query.push(("code_challenge_method", challenge.method().as_str()));
+
+        query
     }
 
-    fn sign_in_up_body<'a>(
+    fn sign_in_up_body<'a, D>(
         &'a self,
         email_or_phone: &'a EmailOrPhone,
         password: &'a impl AsRef<str>,
-    ) -> SignInUpBody<'a> {
+        captcha_token: Option<&'a str>,
+    ) -> SignInUpBody<'a, D> {
+        let gotrue_meta_security =
+            captcha_token.map(|captcha_token| GotrueMetaSecurity { captcha_token });
+
         match email_or_phone {
             EmailOrPhone::Email(email) => SignInUpBody {
                 email: Some(email),
                 phone: None,
                 password: password.as_ref(),
+                data: None,
+                channel: None,
+                gotrue_meta_security,
             },
             EmailOrPhone::Phone(phone) => SignInUpBody {
                 email: None,
                 phone: Some(phone.as_str()),
                 password: password.as_ref(),
+                data: None,
+                channel: None,
+                gotrue_meta_security,
             },
         }
     }
 }
 
-impl Api for ApiClient {
+impl<T: AuthTypes> Api<T> for ApiClient<T> {
     /// Signs up a new user.
     ///
     /// # Arguments
     ///
     /// * `email_or_phone` - The user's email or phone number.
     /// * `password` - The user's password.
+    /// * `options` - The initial user metadata, the confirmation redirect URL, the OTP channel
+    ///   for phone sign-ups and the captcha token.
     ///
     /// # Returns
     ///
@@ -124,9 +327,22 @@ impl Api for ApiClient {
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Sized + Send,
-    ) -> Result<SignUpResponse, ApiError> {
+        options: &SignUpOptions<T>,
+    ) -> Result<SignUpResponse<T>, ApiError> {
+        let query = options
+            .email_redirect_to
+            .as_deref()
+            .map(|redirect_to| vec![("redirect_to", redirect_to)])
+            .unwrap_or_default();
+
+        let mut body =
+            self.sign_in_up_body(&email_or_phone, &password, options.captcha_token.as_deref());
+        body.data = options.data.as_ref();
+        body.channel = options.channel;
+
         self.send_request::<_, _, ApiErrorResponse>(Method::POST, "signup")
-            .body(&self.sign_in_up_body(&email_or_phone, &password))
+            .query(&query)
+            .body(&body)
             .send()
             .await
     }
@@ -136,40 +352,130 @@ impl Api for ApiClient {
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str>,
-    ) -> Result<Session, ApiError> {
+        captcha_token: Option<&str>,
+    ) -> Result<Session<T>, ApiError> {
         self.send_request::<_, _, ApiErrorResponse>(Method::POST, "token")
             .query(&[("grant_type", "password")])
-            .body(&self.sign_in_up_body(&email_or_phone, &password))
+            .body(&self.sign_in_up_body::<()>(&email_or_phone, &password, captcha_token))
             .send()
             .await
     }
 
-    #[instrument(skip(self, access_token))]
-    async fn logout(&self, access_token: impl AsRef<str>) -> Result<(), ApiError> {
-        let endpoint = self.url.join("logout")?;
+    #[instrument(skip(self, credentials), fields(provider = ?credentials.provider))]
+    async fn sign_in_with_id_token(
+        &self,
+        credentials: &IdTokenCredentials,
+    ) -> Result<Session<T>, ApiError> {
+        self.send_request::<_, _, TokenErrorResponse>(Method::POST, "token")
+            .query(&[("grant_type", "id_token")])
+            .body(credentials)
+            .send()
+            .await
+    }
 
-        self.client
-            .post(endpoint)
-            .headers((*self.headers).clone())
-            .bearer_auth(access_token.as_ref())
+    #[instrument(skip(self, data, captcha_token))]
+    async fn sign_in_anonymously(
+        &self,
+        data: Option<&T::UserData>,
+        captcha_token: Option<&str>,
+    ) -> Result<Session<T>, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::POST, "signup")
+            .body(&AnonymousSignInBody {
+                data,
+                gotrue_meta_security: captcha_token
+                    .map(|captcha_token| GotrueMetaSecurity { captcha_token }),
+            })
             .send()
-            .await?
-            .error_for_status()?;
+            .await
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn logout(
+        &self,
+        access_token: impl AsRef<str>,
+        scope: LogoutScope,
+    ) -> Result<(), ApiError> {
+        let request = self.request::<()>(
+            Method::POST,
+            "logout",
+            &[("scope", scope.as_str())],
+            None,
+            Some(access_token.as_ref()),
+        )?;
+
+        self.execute::<ApiErrorResponse>("logout", request, Retry::Never)
+            .await?;
 
         Ok(())
     }
 
     #[instrument(skip(self, access_token))]
-    async fn get_user(&self, access_token: impl AsRef<str>) -> Result<User, ApiError> {
+    async fn get_user(&self, access_token: impl AsRef<str>) -> Result<User<T>, ApiError> {
         self.send_request::<_, (), ApiErrorResponse>(Method::GET, "user")
+            .access_token(access_token.as_ref())
+            .retry(Retry::Idempotent)
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token, attributes))]
+    async fn update_user(
+        &self,
+        access_token: impl AsRef<str>,
+        attributes: &UserAttributes<T>,
+    ) -> Result<User<T>, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::PUT, "user")
+            .body(attributes)
             .access_token(access_token.as_ref())
             .send()
             .await
     }
 
+    #[instrument(skip(self, request), fields(kind = ?request.kind))]
+    async fn resend(&self, request: &ResendRequest) -> Result<(), ApiError> {
+        let query = request
+            .email_redirect_to
+            .as_deref()
+            .map(|redirect_to| vec![("redirect_to", redirect_to)])
+            .unwrap_or_default();
+
+        self.send_request::<IgnoredAny, _, ApiErrorResponse>(Method::POST, "resend")
+            .query(&query)
+            .body(&ResendBody {
+                request,
+                gotrue_meta_security: request
+                    .captcha_token
+                    .as_deref()
+                    .map(|captcha_token| GotrueMetaSecurity { captcha_token }),
+            })
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn reauthenticate(&self, access_token: impl AsRef<str>) -> Result<(), ApiError> {
+        self.send_request::<IgnoredAny, (), ApiErrorResponse>(Method::GET, "reauthenticate")
+            .access_token(access_token.as_ref())
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn settings(&self) -> Result<Settings, ApiError> {
+        self.send_request::<_, (), ApiErrorResponse>(Method::GET, "settings")
+            .retry(Retry::Idempotent)
+            .send()
+            .await
+    }
+
     #[instrument(skip(self))]
     async fn health_check(&self) -> Result<HealthCheckResponse, ApiError> {
         self.send_request::<_, (), ApiErrorResponse>(Method::GET, "health")
+            .retry(Retry::Idempotent)
             .send()
             .await
     }
@@ -178,12 +484,13 @@ impl Api for ApiClient {
     async fn refresh_access_token(
         &self,
         refresh_token: impl AsRef<str>,
-    ) -> Result<Session, ApiError> {
+    ) -> Result<Session<T>, ApiError> {
         self.send_request::<_, _, OAuthErrorResponse>(Method::POST, "token")
             .query(&[("grant_type", "refresh_token")])
             .body(&json!({
                 "refresh_token": refresh_token.as_ref(),
             }))
+            .retry(Retry::Within(self.retry_policy.refresh_reuse_interval))
             .send()
             .await
     }
@@ -192,35 +499,296 @@ impl Api for ApiClient {
     async fn list_users_query(
         &self,
         access_token: impl AsRef<str>,
-        query: &[(&str, &str)],
-    ) -> Result<UserList, ApiError> {
-        self.send_request::<_, (), ApiErrorResponse>(Method::GET, "admin/users")
-            .query(query)
+        query: &ListUsersQuery,
+    ) -> Result<UserList<T>, ApiError> {
+        let page = query.page.map(|page| page.to_string());
+        let per_page = query.per_page.map(|per_page| per_page.to_string());
+        let pairs: Vec<(&str, &str)> = [
+            ("page", page.as_deref()),
+            ("per_page", per_page.as_deref()),
+            ("filter", query.filter.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect();
+
+        let request = self.request::<()>(
+            Method::GET,
+            "admin/users",
+            &pairs,
+            None,
+            Some(access_token.as_ref()),
+        )?;
+        let response = self
+            .execute::<ApiErrorResponse>("admin/users", request, Retry::Idempotent)
+            .await?;
+
+        let total = response
+            .headers()
+            .get("x-total-count")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let next_page = response
+            .headers()
+            .get("link")
+            .and_then(|value| value.to_str().ok())
+            .and_then(next_page_from_link);
+
+        let mut users: UserList<T> = serde_json::from_slice(response.body())?;
+        users.total = total;
+        users.next_page = next_page;
+
+        Ok(users)
+    }
+
+    #[instrument(skip(self, access_token, attributes))]
+    async fn admin_create_user(
+        &self,
+        access_token: impl AsRef<str>,
+        attributes: &AdminUserAttributes<T>,
+    ) -> Result<User<T>, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::POST, "admin/users")
+            .body(attributes)
             .access_token(access_token.as_ref())
             .send()
             .await
     }
 
-    fn create_pkce_oauth_url(&self, req: OAuthRequest, challenge: PkceCodeChallenge) -> Url {
-        let query = format!(
-            "provider={}&redirect_to={}&code_challenge={}&code_challenge_method={}",
-            req.provider,
-            req.redirect_to,
-            challenge.as_str(),
-            challenge.method().as_str()
-        );
+    #[instrument(skip(self, access_token))]
+    async fn admin_get_user(
+        &self,
+        access_token: impl AsRef<str>,
+        user_id: Uuid,
+    ) -> Result<User<T>, ApiError> {
+        self.send_request::<_, (), ApiErrorResponse>(Method::GET, &format!("admin/users/{user_id}"))
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
 
+    #[instrument(skip(self, access_token, attributes))]
+    async fn admin_update_user(
+        &self,
+        access_token: impl AsRef<str>,
+        user_id: Uuid,
+        attributes: &AdminUserAttributes<T>,
+    ) -> Result<User<T>, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::PUT, &format!("admin/users/{user_id}"))
+            .body(attributes)
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn admin_delete_user(
+        &self,
+        access_token: impl AsRef<str>,
+        user_id: Uuid,
+        soft_delete: bool,
+    ) -> Result<(), ApiError> {
+        self.send_request::<IgnoredAny, _, ApiErrorResponse>(
+            Method::DELETE,
+            &format!("admin/users/{user_id}"),
+        )
+        .body(&json!({
+            "should_soft_delete": soft_delete,
+        }))
+        .access_token(access_token.as_ref())
+        .send()
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, access_token, request))]
+    async fn invite_user(
+        &self,
+        access_token: impl AsRef<str>,
+        request: &InviteUserRequest<T>,
+    ) -> Result<User<T>, ApiError> {
+        let query = request
+            .redirect_to
+            .as_deref()
+            .map(|redirect_to| vec![("redirect_to", redirect_to)])
+            .unwrap_or_default();
+
+        self.send_request::<_, _, ApiErrorResponse>(Method::POST, "invite")
+            .query(&query)
+            .body(request)
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token, request))]
+    async fn generate_link(
+        &self,
+        access_token: impl AsRef<str>,
+        request: &GenerateLinkRequest<T>,
+    ) -> Result<GenerateLinkResponse<T>, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::POST, "admin/generate_link")
+            .body(request)
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn admin_list_sso_providers(
+        &self,
+        access_token: impl AsRef<str>,
+    ) -> Result<SsoProviderList, ApiError> {
+        self.send_request::<_, (), ApiErrorResponse>(Method::GET, "admin/sso/providers")
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token, attributes))]
+    async fn admin_create_sso_provider(
+        &self,
+        access_token: impl AsRef<str>,
+        attributes: &SsoProviderAttributes,
+    ) -> Result<SsoProvider, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(Method::POST, "admin/sso/providers")
+            .body(&CreateSsoProviderBody {
+                provider_type: "saml",
+                attributes,
+            })
+            .access_token(access_token.as_ref())
+            .send()
+            .await
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn admin_get_sso_provider(
+        &self,
+        access_token: impl AsRef<str>,
+        provider_id: Uuid,
+    ) -> Result<SsoProvider, ApiError> {
+        self.send_request::<_, (), ApiErrorResponse>(
+            Method::GET,
+            &format!("admin/sso/providers/{provider_id}"),
+        )
+        .access_token(access_token.as_ref())
+        .send()
+        .await
+    }
+
+    #[instrument(skip(self, access_token, attributes))]
+    async fn admin_update_sso_provider(
+        &self,
+        access_token: impl AsRef<str>,
+        provider_id: Uuid,
+        attributes: &SsoProviderAttributes,
+    ) -> Result<SsoProvider, ApiError> {
+        self.send_request::<_, _, ApiErrorResponse>(
+            Method::PUT,
+            &format!("admin/sso/providers/{provider_id}"),
+        )
+        .body(attributes)
+        .access_token(access_token.as_ref())
+        .send()
+        .await
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn admin_delete_sso_provider(
+        &self,
+        access_token: impl AsRef<str>,
+        provider_id: Uuid,
+    ) -> Result<SsoProvider, ApiError> {
+        self.send_request::<_, (), ApiErrorResponse>(
+            Method::DELETE,
+            &format!("admin/sso/providers/{provider_id}"),
+        )
+        .access_token(access_token.as_ref())
+        .send()
+        .await
+    }
+
+    fn create_pkce_oauth_url(&self, req: OAuthRequest, challenge: PkceCodeChallenge) -> Url {
         let mut endpoint = self.url.join("authorize").unwrap();
-        endpoint.set_query(Some(&query));
+        endpoint
+            .query_pairs_mut()
+            .extend_pairs(Self::pkce_oauth_query(&req, &challenge));
 
         endpoint
     }
 
+    #[instrument(skip(self, challenge))]
+    async fn create_pkce_sso_url(
+        &self,
+        req: SsoRequest,
+        challenge: PkceCodeChallenge,
+    ) -> Result<Url, ApiError> {
+        let (domain, provider_id) = match &req.target {
+            SsoTarget::Domain(domain) => (Some(domain.as_str()), None),
+            SsoTarget::ProviderId(provider_id) => (None, Some(*provider_id)),
+        };
+
+        let response = self
+            .send_request::<AuthorizeUrlResponse, _, ApiErrorResponse>(Method::POST, "sso")
+            .body(&SsoBody {
+                domain,
+                provider_id,
+                redirect_to: req.redirect_to.as_deref(),
+                skip_http_redirect: true,
+                code_challenge: challenge.as_str(),
+                code_challenge_method: challenge.method().as_str(),
+            })
+            .send()
+            .await?;
+
+        Ok(response.url)
+    }
+
+    #[instrument(skip(self, access_token, challenge))]
+    async fn create_pkce_link_identity_url(
+        &self,
+        access_token: impl AsRef<str>,
+        req: OAuthRequest,
+        challenge: PkceCodeChallenge,
+    ) -> Result<Url, ApiError> {
+        let mut query = Self::pkce_oauth_query(&req, &challenge);
+        query.push(("skip_http_redirect", "true"));
+
+        let response = self
+            .send_request::<AuthorizeUrlResponse, (), ApiErrorResponse>(
+                Method::GET,
+                "user/identities/authorize",
+            )
+            .query(&query)
+            .access_token(access_token.as_ref())
+            .send()
+            .await?;
+
+        Ok(response.url)
+    }
+
+    #[instrument(skip(self, access_token))]
+    async fn unlink_identity(
+        &self,
+        access_token: impl AsRef<str>,
+        identity_id: Uuid,
+    ) -> Result<(), ApiError> {
+        self.send_request::<IgnoredAny, (), ApiErrorResponse>(
+            Method::DELETE,
+            &format!("user/identities/{identity_id}"),
+        )
+        .access_token(access_token.as_ref())
+        .send()
+        .await?;
+
+        Ok(())
+    }
+
     async fn exchange_code_for_session(
         &self,
         code: &str,
         verifier: &PkceCodeVerifier,
-    ) -> Result<Session, ApiError> {
+    ) -> Result<Session<T>, ApiError> {
         self.send_request::<_, _, ApiErrorResponse>(Method::POST, "token")
             .query(&[("grant_type", "pkce")])
             .body(&json!({
@@ -232,33 +800,177 @@ impl Api for ApiClient {
     }
 }
 
-trait HandleApiResponse {
-    async fn handle_response<T, E>(self) -> Result<T, ApiError>
-    where
-        T: DeserializeOwned,
-        E: DeserializeOwned + IntoApi + Debug;
+/// Extracts the `page` of the `rel="next"` entry from a `Link` header, e.g.
+/// `</admin/users?page=2&per_page=50>; rel="next", </admin/users?page=4&per_page=50>; rel="last"`.
+fn next_page_from_link(link: &str) -> Option<u32> {
+    link.split(',')
+        .find(|entry| entry.contains(r#"rel="next""#))
+        .and_then(|entry| entry.split_once('<')?.1.split_once('>'))
+        .and_then(|(uri, _)| uri.split_once('?'))
+        .and_then(|(_, query)| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "page")
+                .and_then(|(_, page)| page.parse().ok())
+        })
 }
 
-impl HandleApiResponse for Response {
-    async fn handle_response<T, E>(self) -> Result<T, ApiError>
-    where
-        T: DeserializeOwned,
-        E: DeserializeOwned + IntoApi + Debug,
-    {
-        let status = self.status();
-        tracing::Span::current().record("status", status.as_u16());
-
-        match self.error_for_status_ref() {
-            Ok(_) => Ok(self.json::<T>().await?),
-            Err(err) => {
-                if let Ok(body) = self.json::<E>().await {
-                    trace!(%err, body = ?body, "Request failed");
-                    Err(body.with_status(status))
-                } else {
-                    warn!(%err, "Request failed with unhandled HTTP error");
-                    Err(ApiError::UnknownHTTP(err))
-                }
-            }
+/// Parses a `Retry-After` header, given either in seconds or as HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = OffsetDateTime::parse(value.trim(), &Rfc2822).ok()?;
+    Some(
+        (date - OffsetDateTime::now_utc())
+            .try_into()
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the response if successful, the API error of its body otherwise.
+fn handle_response<E>(response: Response<Bytes>) -> Result<Response<Bytes>, ApiError>
+where
+    E: DeserializeOwned + IntoApi + Debug,
+{
+    let status = response.status();
+    tracing::Span::current().record("status", status.as_u16());
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let code = serde_json::from_slice::<ApiErrorResponse>(response.body())
+            .map(|body| body.error_code)
+            .unwrap_or(ApiErrorCode::OverRequestRateLimit);
+        trace!(%code, ?retry_after, "Request was rate limited");
+        return Err(ApiError::RateLimited { code, retry_after });
+    }
+
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    match serde_json::from_slice::<E>(response.body()) {
+        Ok(body) => {
+            trace!(%status, body = ?body, "Request failed");
+            Err(body.with_status(status))
         }
+        Err(_) => {
+            warn!(%status, "Request failed with unhandled HTTP error");
+            Err(ApiError::UnknownHTTP(status))
+        }
+    }
+}
+
+/// Copies `request` for another attempt. Extensions are not copied.
+fn clone_request(request: &Request<Bytes>) -> Request<Bytes> {
+    let mut clone = Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+
+    clone
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ResendType;
+
+    #[test]
+    fn sign_in_up_body_carries_captcha_token() {
+        let client: ApiClient = ApiClient::new(
+            "http://localhost:9999".try_into().unwrap(),
+            Duration::from_secs(1),
+            "api_key",
+        );
+        let email = EmailOrPhone::Email("user@example.com".to_string());
+
+        let body = client.sign_in_up_body::<()>(&email, &"password", Some("captcha"));
+
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({
+                "email": "user@example.com",
+                "password": "password",
+                "gotrue_meta_security": { "captcha_token": "captcha" },
+            })
+        );
+    }
+
+    #[test]
+    fn resend_body_flattens_target() {
+        let request = ResendRequest::builder()
+            .kind(ResendType::PhoneChange)
+            .target(EmailOrPhone::Phone("+15555550100".to_string()))
+            .email_redirect_to("https://example.com")
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "type": "phone_change", "phone": "+15555550100" })
+        );
+    }
+
+    #[test]
+    fn pkce_oauth_url_encodes_redirect_to() {
+        let client: ApiClient = ApiClient::new(
+            "http://localhost:9999/auth/v1/".try_into().unwrap(),
+            Duration::from_secs(1),
+            "api_key",
+        );
+        let (challenge, _) = PkceCodeChallenge::new_random_sha256();
+
+        let url = client.create_pkce_oauth_url(
+            OAuthRequest {
+                provider: "github".to_string(),
+                redirect_to: Some("https://example.com/login/confirm?next=/".to_string()),
+            },
+            challenge,
+        );
+
+        assert_eq!(url.path(), "/auth/v1/authorize");
+        assert!(url
+            .query()
+            .unwrap()
+            .contains("redirect_to=https%3A%2F%2Fexample.com%2Flogin%2Fconfirm%3Fnext%3D%2F"));
+    }
+
+    #[test]
+    fn next_page_from_link_header() {
+        let link = r#"</admin/users?page=2&per_page=50>; rel="next", </admin/users?page=4&per_page=50>; rel="last""#;
+
+        assert_eq!(next_page_from_link(link), Some(2));
+    }
+
+    #[test]
+    fn next_page_from_link_header_last_page() {
+        let link = r#"</admin/users?page=4&per_page=50>; rel="last""#;
+
+        assert_eq!(next_page_from_link(link), None);
+    }
+
+    #[test]
+    fn builder_rejects_invalid_api_key() {
+        let result = ApiClient::<DefaultAuthTypes>::builder()
+            .url("http://localhost:9999".try_into().unwrap())
+            .api_key("invalid\nkey")
+            .build();
+
+        assert!(matches!(result, Err(ClientBuildError::InvalidApiKey(_))));
+    }
+
+    #[test]
+    fn retry_after_seconds_or_date() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
     }
 }
@@ -1,7 +1,10 @@
+use super::TransportError;
+use axum::http::header::InvalidHeaderValue;
 use axum::http::StatusCode;
 use serde::Deserialize;
 use std::fmt;
 use std::fmt::Formatter;
+use std::time::Duration;
 use thiserror::Error;
 
 /// https://github.com/supabase/auth/blob/master/internal/api/errors.go
@@ -11,10 +14,51 @@ pub enum ApiError {
     Request(StatusCode, ApiErrorCode, String),
     #[error("OAuth request failed with status code {0}, error {1:?}, message {2}")]
     OAuth(StatusCode, OAuthErrorCode, String),
+    #[error("Request failed with unhandled HTTP status {0}")]
+    UnknownHTTP(StatusCode),
     #[error(transparent)]
-    UnknownHTTP(#[from] reqwest::Error),
+    Transport(#[from] TransportError),
+    #[error("Invalid request: {0}")]
+    InvalidRequest(#[from] axum::http::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("URL parsing error: {0}")]
     UrlError(#[from] url::ParseError),
+    /// `code` is GoTrue's error code, e.g. `over_email_send_rate_limit`, or
+    /// `over_request_rate_limit` if the response has none.
+    #[error("Rate limited with '{code}', retry after {retry_after:?}")]
+    RateLimited {
+        code: ApiErrorCode,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl ApiError {
+    /// Whether the request may succeed when retried: connection errors, timeouts, 5xx responses
+    /// and rate limits.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Request(status, _, _) | ApiError::OAuth(status, _, _) => {
+                status.is_server_error()
+            }
+            ApiError::UnknownHTTP(status) => status.is_server_error(),
+            ApiError::Transport(e) => e.is_transient(),
+            ApiError::RateLimited { .. } => true,
+            ApiError::InvalidRequest(_) | ApiError::Json(_) | ApiError::UrlError(_) => false,
+        }
+    }
+}
+
+/// Error building an [`ApiClient`](super::ApiClient).
+#[derive(Debug, Error)]
+pub enum ClientBuildError {
+    #[error("Invalid API key header: {0}")]
+    InvalidApiKey(#[from] InvalidHeaderValue),
+    #[cfg(feature = "reqwest")]
+    #[error("Could not build HTTP client: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("No HTTP transport, enable the `reqwest` feature or pass one")]
+    MissingTransport,
 }
 
 pub trait IntoApi {
@@ -39,9 +83,31 @@ impl IntoApi for ApiErrorResponse {
 #[non_exhaustive]
 pub enum ApiErrorCode {
     SignupDisabled,
+    EmailProviderDisabled,
+    PhoneProviderDisabled,
+    EmailAddressInvalid,
+    ValidationFailed,
     UserAlreadyExists,
     BadJwt,
     InvalidCredentials,
+    NotAdmin,
+    UserNotFound,
+    EmailExists,
+    PhoneExists,
+    IdentityNotFound,
+    IdentityAlreadyExists,
+    SingleIdentityNotDeletable,
+    ManualLinkingDisabled,
+    AnonymousProviderDisabled,
+    WeakPassword,
+    SamePassword,
+    SsoProviderNotFound,
+    SamlProviderDisabled,
+    ReauthenticationNeeded,
+    ReauthenticationNotValid,
+    OverEmailSendRateLimit,
+    OverSmsSendRateLimit,
+    OverRequestRateLimit,
     #[serde(untagged)]
     Unknown(String),
 }
@@ -78,6 +144,24 @@ pub enum OAuthErrorCode {
     Unknown(String),
 }
 
+/// Error body of the `/token` endpoint, which depending on the grant type and failure responds
+/// with either an API or an OAuth error.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum TokenErrorResponse {
+    Api(ApiErrorResponse),
+    OAuth(OAuthErrorResponse),
+}
+
+impl IntoApi for TokenErrorResponse {
+    fn with_status(self, status_code: StatusCode) -> ApiError {
+        match self {
+            TokenErrorResponse::Api(response) => response.with_status(status_code),
+            TokenErrorResponse::OAuth(response) => response.with_status(status_code),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,4 +174,22 @@ mod tests {
 
         assert_eq!(result.error, OAuthErrorCode::InvalidGrant);
     }
+
+    #[test]
+    fn token_error_response_either_shape() {
+        let oauth = r#"{"error":"invalid_request","error_description":"Bad ID token"}"#;
+        let api = r#"{"code":400,"error_code":"validation_failed","msg":"Unsupported provider"}"#;
+
+        let oauth: TokenErrorResponse = serde_json::from_str(oauth).unwrap();
+        let api: TokenErrorResponse = serde_json::from_str(api).unwrap();
+
+        assert!(matches!(
+            oauth.with_status(StatusCode::BAD_REQUEST),
+            ApiError::OAuth(StatusCode::BAD_REQUEST, OAuthErrorCode::Unknown(_), _)
+        ));
+        assert!(matches!(
+            api.with_status(StatusCode::BAD_REQUEST),
+            ApiError::Request(StatusCode::BAD_REQUEST, ApiErrorCode::ValidationFailed, _)
+        ));
+    }
 }
@@ -1,22 +1,30 @@
 mod client;
 mod errors;
+mod retry;
+mod transport;
 mod types;
 
 use super::types::*;
+use crate::{AuthTypes, DefaultAuthTypes};
 pub use client::*;
 pub use errors::*;
 use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+pub(crate) use retry::Retry;
+pub use retry::RetryPolicy;
 use std::future::Future;
+pub use transport::*;
 pub use types::*;
 use url::Url;
+use uuid::Uuid;
 
-pub trait Api {
+pub trait Api<T: AuthTypes = DefaultAuthTypes> {
     /// Signs up a new user.
     ///
     /// # Arguments
     ///
     /// * `email_or_phone` - The user's email or phone number.
     /// * `password` - The user's password.
+    /// * `options` - User metadata, confirmation redirect, OTP channel and captcha token.
     ///
     /// # Returns
     ///
@@ -25,46 +33,182 @@ pub trait Api {
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Sized + Send,
-    ) -> impl Future<Output = Result<SignUpResponse, ApiError>> + Send;
+        options: &SignUpOptions<T>,
+    ) -> impl Future<Output = Result<SignUpResponse<T>, ApiError>> + Send;
 
     fn sign_in(
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Send,
-    ) -> impl Future<Output = Result<Session, ApiError>>;
+        captcha_token: Option<&str>,
+    ) -> impl Future<Output = Result<Session<T>, ApiError>>;
 
-    fn logout(&self, access_token: impl AsRef<str>) -> impl Future<Output = Result<(), ApiError>>;
+    /// Signs in with an OIDC ID token issued by `credentials.provider`.
+    fn sign_in_with_id_token(
+        &self,
+        credentials: &IdTokenCredentials,
+    ) -> impl Future<Output = Result<Session<T>, ApiError>>;
+
+    /// Signs in a new anonymous user. Always returns a session.
+    fn sign_in_anonymously(
+        &self,
+        data: Option<&T::UserData>,
+        captcha_token: Option<&str>,
+    ) -> impl Future<Output = Result<Session<T>, ApiError>>;
+
+    fn logout(
+        &self,
+        access_token: impl AsRef<str>,
+        scope: LogoutScope,
+    ) -> impl Future<Output = Result<(), ApiError>>;
 
     fn get_user(
         &self,
         access_token: impl AsRef<str>,
-    ) -> impl Future<Output = Result<User, ApiError>>;
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    fn update_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        attributes: &UserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    /// Resends a signup confirmation, email change or phone OTP.
+    fn resend(&self, request: &ResendRequest) -> impl Future<Output = Result<(), ApiError>>;
+
+    /// Sends a nonce to the user's email or phone, required for sensitive updates such as
+    /// password changes when secure password change is enabled.
+    fn reauthenticate(
+        &self,
+        access_token: impl AsRef<str> + Send,
+    ) -> impl Future<Output = Result<(), ApiError>>;
+
+    /// Returns the public settings, e.g. which external providers are enabled.
+    fn settings(&self) -> impl Future<Output = Result<Settings, ApiError>>;
 
     fn health_check(&self) -> impl Future<Output = Result<HealthCheckResponse, ApiError>>;
 
     fn refresh_access_token(
         &self,
         refresh_token: impl AsRef<str>,
-    ) -> impl Future<Output = Result<Session, ApiError>>;
+    ) -> impl Future<Output = Result<Session<T>, ApiError>>;
 
     fn list_users(
         &self,
         access_token: impl AsRef<str> + Send,
-    ) -> impl Future<Output = Result<UserList, ApiError>> {
-        self.list_users_query(access_token, &[])
+    ) -> impl Future<Output = Result<UserList<T>, ApiError>> {
+        async move {
+            self.list_users_query(access_token, &ListUsersQuery::default())
+                .await
+        }
     }
 
+    /// Lists a single page of users. `total` and `next_page` of the returned
+    /// [`UserList`] are filled from the pagination headers.
     fn list_users_query(
         &self,
         access_token: impl AsRef<str> + Send,
-        query: &[(&str, &str)],
-    ) -> impl Future<Output = Result<UserList, ApiError>>;
+        query: &ListUsersQuery,
+    ) -> impl Future<Output = Result<UserList<T>, ApiError>>;
+
+    fn admin_create_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        attributes: &AdminUserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    fn admin_get_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        user_id: Uuid,
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    fn admin_update_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        user_id: Uuid,
+        attributes: &AdminUserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    /// Deletes a user. A soft deleted user is obfuscated and can not sign in anymore, but
+    /// remains in the database.
+    fn admin_delete_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        user_id: Uuid,
+        soft_delete: bool,
+    ) -> impl Future<Output = Result<(), ApiError>>;
+
+    /// Invites a user by email. GoTrue sends the invite mail.
+    fn invite_user(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        request: &InviteUserRequest<T>,
+    ) -> impl Future<Output = Result<User<T>, ApiError>>;
+
+    /// Generates an action link without sending any email.
+    fn generate_link(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        request: &GenerateLinkRequest<T>,
+    ) -> impl Future<Output = Result<GenerateLinkResponse<T>, ApiError>>;
+
+    fn admin_list_sso_providers(
+        &self,
+        access_token: impl AsRef<str> + Send,
+    ) -> impl Future<Output = Result<SsoProviderList, ApiError>>;
+
+    fn admin_create_sso_provider(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        attributes: &SsoProviderAttributes,
+    ) -> impl Future<Output = Result<SsoProvider, ApiError>>;
+
+    fn admin_get_sso_provider(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        provider_id: Uuid,
+    ) -> impl Future<Output = Result<SsoProvider, ApiError>>;
+
+    fn admin_update_sso_provider(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        provider_id: Uuid,
+        attributes: &SsoProviderAttributes,
+    ) -> impl Future<Output = Result<SsoProvider, ApiError>>;
+
+    fn admin_delete_sso_provider(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        provider_id: Uuid,
+    ) -> impl Future<Output = Result<SsoProvider, ApiError>>;
 
     fn create_pkce_oauth_url(&self, req: OAuthRequest, challenge: PkceCodeChallenge) -> Url;
 
+    /// Returns the PKCE authorize URL of the SSO identity provider selected by `req`.
+    fn create_pkce_sso_url(
+        &self,
+        req: SsoRequest,
+        challenge: PkceCodeChallenge,
+    ) -> impl Future<Output = Result<Url, ApiError>>;
+
+    /// Returns the PKCE authorize URL for linking a new identity to the signed in user.
+    fn create_pkce_link_identity_url(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        req: OAuthRequest,
+        challenge: PkceCodeChallenge,
+    ) -> impl Future<Output = Result<Url, ApiError>>;
+
+    fn unlink_identity(
+        &self,
+        access_token: impl AsRef<str> + Send,
+        identity_id: Uuid,
+    ) -> impl Future<Output = Result<(), ApiError>>;
+
     fn exchange_code_for_session(
         &self,
         code: &str,
         verifier: &PkceCodeVerifier,
-    ) -> impl Future<Output = Result<Session, ApiError>>;
+    ) -> impl Future<Output = Result<Session<T>, ApiError>>;
 }
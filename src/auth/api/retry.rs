@@ -0,0 +1,69 @@
+use bon::Builder;
+use std::time::Duration;
+
+/// Retries of idempotent requests on connection errors, 5xx responses and rate limits.
+///
+/// The backoff doubles with every retry, starting at `initial_backoff`. A `Retry-After` sent
+/// with a rate limit takes precedence, but is not waited for if it exceeds `max_backoff`.
+#[derive(Debug, Clone, Builder)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, 0 disables retries.
+    #[builder(default = 2)]
+    pub max_retries: u32,
+    #[builder(default = Duration::from_millis(100))]
+    pub initial_backoff: Duration,
+    #[builder(default = Duration::from_secs(5))]
+    pub max_backoff: Duration,
+    /// GoTrue's `GOTRUE_SECURITY_REFRESH_TOKEN_REUSE_INTERVAL`. Refreshing is only retried
+    /// within it, where reusing a refresh token rotated by a lost response still succeeds.
+    #[builder(default = Duration::from_secs(10))]
+    pub refresh_reuse_interval: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries.
+    pub fn none() -> Self {
+        Self::builder().max_retries(0).build()
+    }
+
+    /// Returns the delay before retry number `attempt`, starting at 0.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Whether a request may be retried.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) enum Retry {
+    #[default]
+    Never,
+    Idempotent,
+    /// Retry only while less than the given time passed since the first attempt.
+    Within(Duration),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::builder()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .build();
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(300));
+        assert_eq!(policy.backoff(40), Duration::from_millis(300));
+    }
+}
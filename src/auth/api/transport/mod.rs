@@ -0,0 +1,127 @@
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "tower")]
+mod tower;
+
+#[cfg(feature = "tower")]
+pub use self::tower::TowerTransport;
+use axum::body::Bytes;
+use axum::http::{Request, Response};
+use futures_util::future::BoxFuture;
+use std::fmt;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Sends the HTTP requests of an [`ApiClient`](super::ApiClient).
+///
+/// Implemented for `reqwest::Client` with the default `reqwest` feature, and by
+/// `TowerTransport` with the `tower` feature. Implement it to run on another HTTP stack or to
+/// record requests in tests.
+pub trait HttpTransport: Send + Sync + 'static {
+    /// Sends `request` and returns the response with its body read completely.
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> impl Future<Output = Result<Response<Bytes>, TransportError>> + Send;
+}
+
+/// Error of an [`HttpTransport`] before a complete response was received.
+#[derive(Debug, Error)]
+#[error("{kind} error: {source}")]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    source: BoxError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransportErrorKind {
+    /// No connection could be established.
+    Connect,
+    Timeout,
+    /// The request failed after connecting, e.g. the connection was reset.
+    Request,
+    /// The response body could not be read.
+    Body,
+}
+
+impl fmt::Display for TransportErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            TransportErrorKind::Connect => "Connect",
+            TransportErrorKind::Timeout => "Timeout",
+            TransportErrorKind::Request => "Request",
+            TransportErrorKind::Body => "Body",
+        };
+
+        f.write_str(kind)
+    }
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, source: impl Into<BoxError>) -> Self {
+        Self {
+            kind,
+            source: source.into(),
+        }
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    /// Whether the request may succeed when retried.
+    pub fn is_transient(&self) -> bool {
+        !matches!(self.kind, TransportErrorKind::Body)
+    }
+}
+
+/// Type-erased [`HttpTransport`], so [`ApiClient`](super::ApiClient) is not generic over it.
+/// Every transport converts into it, e.g. to pass a `reqwest::Client` to
+/// [`ApiClient::builder`](super::ApiClient::builder).
+#[derive(Clone)]
+pub struct DynTransport {
+    inner: Arc<dyn ErasedTransport>,
+}
+
+impl DynTransport {
+    pub fn new(transport: impl HttpTransport) -> Self {
+        Self {
+            inner: Arc::new(transport),
+        }
+    }
+
+    pub(crate) fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+        self.inner.send(request)
+    }
+}
+
+impl<H: HttpTransport> From<H> for DynTransport {
+    fn from(transport: H) -> Self {
+        Self::new(transport)
+    }
+}
+
+/// Object-safe counterpart of [`HttpTransport`].
+trait ErasedTransport: Send + Sync {
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>>;
+}
+
+impl<H: HttpTransport> ErasedTransport for H {
+    fn send(
+        &self,
+        request: Request<Bytes>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+        Box::pin(HttpTransport::send(self, request))
+    }
+}
@@ -0,0 +1,36 @@
+use super::{HttpTransport, TransportError, TransportErrorKind};
+use axum::body::Bytes;
+use axum::http::{Request, Response};
+
+impl HttpTransport for reqwest::Client {
+    async fn send(&self, request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        let request = reqwest::Request::try_from(request).map_err(transport_error)?;
+        let response = self.execute(request).await.map_err(transport_error)?;
+
+        let mut builder = Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = response.headers().clone();
+        }
+        let body = response.bytes().await.map_err(transport_error)?;
+
+        builder
+            .body(body)
+            .map_err(|e| TransportError::new(TransportErrorKind::Body, e))
+    }
+}
+
+fn transport_error(e: reqwest::Error) -> TransportError {
+    let kind = if e.is_timeout() {
+        TransportErrorKind::Timeout
+    } else if e.is_connect() {
+        TransportErrorKind::Connect
+    } else if e.is_body() || e.is_decode() {
+        TransportErrorKind::Body
+    } else {
+        TransportErrorKind::Request
+    };
+
+    TransportError::new(kind, e)
+}
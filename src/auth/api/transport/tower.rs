@@ -0,0 +1,80 @@
+use super::{BoxError, HttpTransport, TransportError, TransportErrorKind};
+use axum::body::{Bytes, HttpBody};
+use axum::http::{Request, Response};
+use http_body_util::{BodyExt, Full};
+use std::error::Error;
+use std::io;
+use tower::timeout::error::Elapsed;
+use tower::{Service, ServiceExt};
+
+/// [`HttpTransport`] sending requests through a [`tower::Service`], e.g. a `hyper-util` client
+/// wrapped in the application's HTTP client middleware.
+///
+/// Timeouts are left to the service, e.g. a `tower::timeout::Timeout` layer. Its errors are
+/// reported as [`TransportErrorKind::Timeout`], refused or unreachable connections as
+/// [`TransportErrorKind::Connect`].
+#[derive(Debug, Clone)]
+pub struct TowerTransport<S> {
+    service: S,
+}
+
+impl<S> TowerTransport<S> {
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+impl<S, B> HttpTransport for TowerTransport<S>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<B>> + Clone + Send + Sync + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    async fn send(&self, request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        let response = self
+            .service
+            .clone()
+            .oneshot(request.map(Full::new))
+            .await
+            .map_err(|e| transport_error(e.into()))?;
+
+        let (parts, body) = response.into_parts();
+        let body = body
+            .collect()
+            .await
+            .map_err(|e| TransportError::new(TransportErrorKind::Body, e))?
+            .to_bytes();
+
+        Ok(Response::from_parts(parts, body))
+    }
+}
+
+/// Classifies `e` by the errors in its source chain, as the service's error type is unknown.
+fn transport_error(e: BoxError) -> TransportError {
+    let mut kind = TransportErrorKind::Request;
+    let mut source: Option<&(dyn Error + 'static)> = Some(&*e);
+    while let Some(error) = source {
+        if error.is::<Elapsed>() {
+            kind = TransportErrorKind::Timeout;
+            break;
+        }
+
+        if let Some(error) = error.downcast_ref::<io::Error>() {
+            match error.kind() {
+                io::ErrorKind::TimedOut => kind = TransportErrorKind::Timeout,
+                io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::AddrNotAvailable
+                | io::ErrorKind::NotConnected => kind = TransportErrorKind::Connect,
+                _ => {}
+            }
+            break;
+        }
+
+        source = error.source();
+    }
+
+    TransportError::new(kind, e)
+}
@@ -1,6 +1,12 @@
-use crate::{Session, User};
+use crate::{AuthTypes, DefaultAuthTypes, EmailOrPhone, Session, User};
+use bon::Builder;
 use either::Either;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
 pub struct HealthCheckResponse {
@@ -9,34 +15,548 @@ pub struct HealthCheckResponse {
     pub description: String,
 }
 
+/// Public GoTrue settings as returned by `GET /settings`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settings {
+    /// Providers by name, e.g. `github`, `email` or `anonymous_users`, and whether they are
+    /// enabled.
+    pub external: BTreeMap<String, bool>,
+    pub disable_signup: bool,
+    pub mailer_autoconfirm: bool,
+    pub phone_autoconfirm: bool,
+    pub sms_provider: String,
+}
+
+impl Settings {
+    pub fn is_provider_enabled(&self, provider: &str) -> bool {
+        self.external.get(provider).copied().unwrap_or(false)
+    }
+
+    pub fn enabled_providers(&self) -> impl Iterator<Item = &str> {
+        self.external
+            .iter()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(provider, _)| provider.as_str())
+    }
+}
+
 #[derive(Serialize)]
-pub struct SignInUpBody<'a> {
+pub struct SignInUpBody<'a, D = ()> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub phone: Option<&'a str>,
     pub password: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<&'a D>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<OtpChannel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gotrue_meta_security: Option<GotrueMetaSecurity<'a>>,
+}
+
+/// Channel the OTP is sent through when signing up with a phone number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpChannel {
+    #[default]
+    Sms,
+    Whatsapp,
+}
+
+/// Options for [`Auth::sign_up`](crate::Auth::sign_up).
+#[derive(Builder)]
+pub struct SignUpOptions<T: AuthTypes = DefaultAuthTypes> {
+    /// Initial `user_metadata`, e.g. display name or locale.
+    pub data: Option<T::UserData>,
+    /// Sent as query parameter, the URL the confirmation link redirects to.
+    #[builder(into)]
+    pub email_redirect_to: Option<String>,
+    pub channel: Option<OtpChannel>,
+    /// Sent as `gotrue_meta_security`, required when captcha protection is enabled.
+    #[builder(into)]
+    pub captcha_token: Option<String>,
+}
+
+impl SignUpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: AuthTypes> Default for SignUpOptions<T> {
+    fn default() -> Self {
+        Self {
+            data: None,
+            email_redirect_to: None,
+            channel: None,
+            captcha_token: None,
+        }
+    }
+}
+
+impl<T: AuthTypes> Debug for SignUpOptions<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignUpOptions")
+            .field("data", &self.data)
+            .field("email_redirect_to", &self.email_redirect_to)
+            .field("channel", &self.channel)
+            .field(
+                "captcha_token",
+                &self.captcha_token.as_ref().map(|_| "[redacted]"),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct GotrueMetaSecurity<'a> {
+    pub captcha_token: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnonymousSignInBody<'a, D> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<&'a D>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gotrue_meta_security: Option<GotrueMetaSecurity<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IdTokenProvider {
+    Google,
+    Apple,
+    Azure,
+    Facebook,
+    Kakao,
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// Credentials for signing in with an OIDC ID token obtained natively, e.g. through
+/// Sign in with Apple or Google Sign-In on mobile devices.
+#[derive(Clone, Serialize, Deserialize, Builder)]
+pub struct IdTokenCredentials {
+    pub provider: IdTokenProvider,
+    #[builder(into)]
+    pub id_token: String,
+    /// Raw nonce, if the ID token was requested with a hashed nonce.
+    #[builder(into)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Access token issued together with the ID token, required if the ID token contains an
+    /// `at_hash` claim.
+    #[builder(into)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+}
+
+impl Debug for IdTokenCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdTokenCredentials")
+            .field("provider", &self.provider)
+            .field("id_token", &"[redacted]")
+            .field("nonce", &self.nonce.as_ref().map(|_| "[redacted]"))
+            .field(
+                "access_token",
+                &self.access_token.as_ref().map(|_| "[redacted]"),
+            )
+            .finish()
+    }
+}
+
+/// Attributes for creating or updating a user through the admin API.
+///
+/// Unset fields are omitted from the request and left untouched by GoTrue.
+#[derive(Serialize, Builder)]
+#[serde(bound = "")]
+pub struct AdminUserAttributes<T: AuthTypes = DefaultAuthTypes> {
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_confirm: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_confirm: Option<bool>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_metadata: Option<T::AppData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<T::UserData>,
+    /// Go duration string (e.g. `24h`), or `none` to lift an existing ban.
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ban_duration: Option<String>,
+}
+
+impl<T: AuthTypes> Default for AdminUserAttributes<T> {
+    fn default() -> Self {
+        Self {
+            email: None,
+            phone: None,
+            password: None,
+            email_confirm: None,
+            phone_confirm: None,
+            role: None,
+            app_metadata: None,
+            user_metadata: None,
+            ban_duration: None,
+        }
+    }
+}
+
+impl<T: AuthTypes> Debug for AdminUserAttributes<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminUserAttributes")
+            .field("email", &self.email)
+            .field("phone", &self.phone)
+            .field("password", &self.password.as_ref().map(|_| "[redacted]"))
+            .field("email_confirm", &self.email_confirm)
+            .field("phone_confirm", &self.phone_confirm)
+            .field("role", &self.role)
+            .field("app_metadata", &self.app_metadata)
+            .field("user_metadata", &self.user_metadata)
+            .field("ban_duration", &self.ban_duration)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for AdminUserAttributes<T>
+where
+    T::AppData: Clone,
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+            password: self.password.clone(),
+            email_confirm: self.email_confirm,
+            phone_confirm: self.phone_confirm,
+            role: self.role.clone(),
+            app_metadata: self.app_metadata.clone(),
+            user_metadata: self.user_metadata.clone(),
+            ban_duration: self.ban_duration.clone(),
+        }
+    }
+}
+
+/// Query for listing users through the admin API.
+#[derive(Debug, Default, Clone, Serialize, Builder)]
+pub struct ListUsersQuery {
+    /// Page to fetch, starting at 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    /// Only return users whose email or name contains this string.
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+#[derive(Serialize, Builder)]
+#[serde(bound = "")]
+pub struct InviteUserRequest<T: AuthTypes = DefaultAuthTypes> {
+    #[builder(into)]
+    pub email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T::UserData>,
+    /// Sent as query parameter, the URL the invite link redirects to.
+    #[builder(into)]
+    #[serde(skip)]
+    pub redirect_to: Option<String>,
+}
+
+impl<T: AuthTypes> Debug for InviteUserRequest<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InviteUserRequest")
+            .field("email", &self.email)
+            .field("data", &self.data)
+            .field("redirect_to", &self.redirect_to)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for InviteUserRequest<T>
+where
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            email: self.email.clone(),
+            data: self.data.clone(),
+            redirect_to: self.redirect_to.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResendType {
+    Signup,
+    EmailChange,
+    Sms,
+    PhoneChange,
+}
+
+/// Resends a signup confirmation, email change or phone OTP.
+#[derive(Debug, Clone, Serialize, Builder)]
+pub struct ResendRequest {
+    #[serde(rename = "type")]
+    pub kind: ResendType,
+    /// Email for `signup` and `email_change`, phone for `sms` and `phone_change`.
+    #[serde(flatten)]
+    pub target: EmailOrPhone,
+    /// Sent as query parameter, the URL the confirmation link redirects to.
+    #[builder(into)]
+    #[serde(skip)]
+    pub email_redirect_to: Option<String>,
+    /// Sent as `gotrue_meta_security`, required when captcha protection is enabled.
+    #[builder(into)]
+    #[serde(skip)]
+    pub captcha_token: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ResendBody<'a> {
+    #[serde(flatten)]
+    pub request: &'a ResendRequest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gotrue_meta_security: Option<GotrueMetaSecurity<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerateLinkType {
+    Signup,
+    Invite,
+    Magiclink,
+    Recovery,
+    /// Link sent to the current address of an email change.
+    EmailChangeCurrent,
+    /// Link sent to the new address of an email change.
+    EmailChangeNew,
+}
+
+#[derive(Serialize, Builder)]
+#[serde(bound = "")]
+pub struct GenerateLinkRequest<T: AuthTypes = DefaultAuthTypes> {
+    #[serde(rename = "type")]
+    pub link_type: GenerateLinkType,
+    #[builder(into)]
+    pub email: String,
+    /// Required for [`GenerateLinkType::Signup`].
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Required for the email change link types.
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T::UserData>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+}
+
+impl<T: AuthTypes> Debug for GenerateLinkRequest<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenerateLinkRequest")
+            .field("link_type", &self.link_type)
+            .field("email", &self.email)
+            .field("password", &self.password.as_ref().map(|_| "[redacted]"))
+            .field("new_email", &self.new_email)
+            .field("data", &self.data)
+            .field("redirect_to", &self.redirect_to)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for GenerateLinkRequest<T>
+where
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            link_type: self.link_type,
+            email: self.email.clone(),
+            password: self.password.clone(),
+            new_email: self.new_email.clone(),
+            data: self.data.clone(),
+            redirect_to: self.redirect_to.clone(),
+        }
+    }
+}
+
+/// A generated action link together with the user it was generated for.
+///
+/// GoTrue does not send any email for generated links, which makes it possible to deliver them
+/// through a custom mailer.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub struct GenerateLinkResponse<T: AuthTypes = DefaultAuthTypes> {
+    pub action_link: String,
+    pub email_otp: String,
+    pub hashed_token: String,
+    pub verification_type: String,
+    pub redirect_to: String,
+    #[serde(flatten)]
+    pub user: User<T>,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(transparent)]
-pub struct SignUpResponse {
+impl<T: AuthTypes> Debug for GenerateLinkResponse<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenerateLinkResponse")
+            .field("action_link", &self.action_link)
+            .field("email_otp", &self.email_otp)
+            .field("hashed_token", &self.hashed_token)
+            .field("verification_type", &self.verification_type)
+            .field("redirect_to", &self.redirect_to)
+            .field("user", &self.user)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for GenerateLinkResponse<T>
+where
+    T::AppData: Clone,
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            action_link: self.action_link.clone(),
+            email_otp: self.email_otp.clone(),
+            hashed_token: self.hashed_token.clone(),
+            verification_type: self.verification_type.clone(),
+            redirect_to: self.redirect_to.clone(),
+            user: self.user.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SsoBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<&'a str>,
+    pub skip_http_redirect: bool,
+    pub code_challenge: &'a str,
+    pub code_challenge_method: &'a str,
+}
+
+/// A SAML 2.0 identity provider used for enterprise SSO.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SsoProvider {
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saml: Option<SamlProvider>,
+    #[serde(default)]
+    pub domains: Vec<SsoDomain>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SamlProvider {
+    pub entity_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_url: Option<String>,
+    #[serde(default)]
+    pub metadata_xml: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute_mapping: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SsoDomain {
+    pub domain: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SsoProviderList {
+    pub items: Vec<SsoProvider>,
+}
+
+/// Attributes for creating or updating a SAML SSO provider. Either `metadata_url` or
+/// `metadata_xml` is required on creation.
+#[derive(Debug, Default, Clone, Serialize, Builder)]
+pub struct SsoProviderAttributes {
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_url: Option<String>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_xml: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domains: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_mapping: Option<serde_json::Value>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateSsoProviderBody<'a> {
+    #[serde(rename = "type")]
+    pub provider_type: &'a str,
+    #[serde(flatten)]
+    pub attributes: &'a SsoProviderAttributes,
+}
+
+/// Response of endpoints that would otherwise redirect, when called with
+/// `skip_http_redirect=true`.
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeUrlResponse {
+    pub url: Url,
+}
+
+#[derive(Deserialize)]
+#[serde(transparent, bound = "")]
+pub struct SignUpResponse<T: AuthTypes = DefaultAuthTypes> {
     #[serde(with = "either::serde_untagged")]
-    inner: Either<User, Session>,
+    inner: Either<User<T>, Session<T>>,
 }
 
-impl SignUpResponse {
-    pub fn session(self) -> Option<Session> {
+impl<T: AuthTypes> SignUpResponse<T> {
+    pub fn session(self) -> Option<Session<T>> {
         self.into()
     }
 
-    pub fn user(self) -> Option<User> {
+    pub fn user(self) -> Option<User<T>> {
         self.into()
     }
 }
 
-impl AsRef<User> for SignUpResponse {
-    fn as_ref(&self) -> &User {
+impl<T: AuthTypes> Debug for SignUpResponse<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignUpResponse")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> AsRef<User<T>> for SignUpResponse<T> {
+    fn as_ref(&self) -> &User<T> {
         match self.inner {
             Either::Left(ref user) => user,
             Either::Right(ref session) => &session.user,
@@ -44,14 +564,30 @@ impl AsRef<User> for SignUpResponse {
     }
 }
 
-impl From<SignUpResponse> for Option<User> {
-    fn from(val: SignUpResponse) -> Self {
+impl<T: AuthTypes> From<SignUpResponse<T>> for Option<User<T>> {
+    fn from(val: SignUpResponse<T>) -> Self {
         val.inner.left()
     }
 }
 
-impl From<SignUpResponse> for Option<Session> {
-    fn from(val: SignUpResponse) -> Self {
+impl<T: AuthTypes> From<SignUpResponse<T>> for Option<Session<T>> {
+    fn from(val: SignUpResponse<T>) -> Self {
         val.inner.right()
     }
 }
+
+impl<T: AuthTypes> From<User<T>> for SignUpResponse<T> {
+    fn from(user: User<T>) -> Self {
+        Self {
+            inner: Either::Left(user),
+        }
+    }
+}
+
+impl<T: AuthTypes> From<Session<T>> for SignUpResponse<T> {
+    fn from(session: Session<T>) -> Self {
+        Self {
+            inner: Either::Right(session),
+        }
+    }
+}
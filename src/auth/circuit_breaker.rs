@@ -0,0 +1,247 @@
+use crate::api::ApiError;
+use bon::Builder;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Configuration of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Builder)]
+pub struct CircuitBreakerConfig {
+    /// Share of failed calls within `window` that opens the circuit, between 0 and 1.
+    #[builder(default = 0.5)]
+    pub failure_rate_threshold: f64,
+    /// Calls within `window` required before the failure rate is evaluated.
+    #[builder(default = 10)]
+    pub minimum_calls: usize,
+    #[builder(default = Duration::from_secs(30))]
+    pub window: Duration,
+    /// How long calls fail fast before GoTrue is probed again.
+    #[builder(default = Duration::from_secs(15))]
+    pub open_duration: Duration,
+    /// Calls let through while half-open. The circuit closes once all of them succeeded.
+    #[builder(default = 1)]
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// GoTrue is healthy, all calls are let through.
+    Closed,
+    /// GoTrue is considered down, calls fail fast with
+    /// [`ClientError::Unavailable`](crate::ClientError::Unavailable).
+    Open,
+    /// The open duration passed, a few probe calls decide whether the circuit closes again.
+    HalfOpen,
+}
+
+/// Circuit breaker around GoTrue calls of an [`AuthService`](crate::AuthService).
+///
+/// Only connection errors, timeouts and 5xx responses count as failures, errors such as wrong
+/// credentials or rate limits do not. Clones share their state, so a clone can be kept to
+/// observe the state, e.g. to skip calls to GoTrue while it is down.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: Arc<CircuitBreakerConfig>,
+    state: Arc<Mutex<State>>,
+}
+
+enum State {
+    Closed {
+        /// Completion time and success of the calls within the window.
+        calls: VecDeque<(Instant, bool)>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        since: Instant,
+        probes: u32,
+        successes: u32,
+    },
+}
+
+/// Permission to call GoTrue, whose outcome is passed to [`CircuitBreaker::record`].
+#[derive(Debug)]
+pub(crate) struct Permit {
+    probe: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            state: Arc::new(Mutex::new(State::closed())),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether calls are let through, i.e. the circuit is not open.
+    pub fn is_available(&self) -> bool {
+        self.state() != CircuitState::Open
+    }
+
+    /// Returns a permit if the call may go through, `None` to fail fast.
+    pub(crate) fn try_acquire(&self) -> Option<Permit> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match &mut *state {
+            State::Closed { .. } => Some(Permit { probe: false }),
+            State::Open { until } if now < *until => None,
+            State::Open { .. } => {
+                *state = State::HalfOpen {
+                    since: now,
+                    probes: 1,
+                    successes: 0,
+                };
+                Some(Permit { probe: true })
+            }
+            State::HalfOpen { since, probes, .. } => {
+                // Probes whose outcome never arrives, e.g. cancelled requests, must not keep the
+                // circuit half-open forever.
+                if *probes >= self.config.half_open_probes
+                    && since.elapsed() < self.config.open_duration
+                {
+                    return None;
+                }
+                if *probes >= self.config.half_open_probes {
+                    *since = now;
+                    *probes = 0;
+                }
+                *probes += 1;
+                Some(Permit { probe: true })
+            }
+        }
+    }
+
+    /// Records the outcome of a call let through by `permit`.
+    pub(crate) fn record(&self, permit: Permit, result: Result<(), &ApiError>) {
+        let failed = result.is_err_and(is_outage);
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        match &mut *state {
+            State::Closed { calls } if !permit.probe => {
+                calls.push_back((now, !failed));
+                while calls
+                    .front()
+                    .is_some_and(|(at, _)| now.duration_since(*at) > self.config.window)
+                {
+                    calls.pop_front();
+                }
+
+                let failures = calls.iter().filter(|(_, success)| !success).count();
+                if calls.len() >= self.config.minimum_calls
+                    && failures as f64 / calls.len() as f64 >= self.config.failure_rate_threshold
+                {
+                    warn!(failures, calls = calls.len(), "GoTrue circuit opened");
+                    *state = State::open(now, &self.config);
+                }
+            }
+            State::HalfOpen { successes, .. } if permit.probe => {
+                if failed {
+                    warn!("GoTrue circuit probe failed, opened again");
+                    *state = State::open(now, &self.config);
+                    return;
+                }
+
+                *successes += 1;
+                if *successes >= self.config.half_open_probes {
+                    info!("GoTrue circuit closed");
+                    *state = State::closed();
+                }
+            }
+            // Calls started before the last state change.
+            _ => {}
+        }
+    }
+}
+
+impl State {
+    fn closed() -> Self {
+        State::Closed {
+            calls: VecDeque::new(),
+        }
+    }
+
+    fn open(now: Instant, config: &CircuitBreakerConfig) -> Self {
+        State::Open {
+            until: now + config.open_duration,
+        }
+    }
+}
+
+/// Whether `error` indicates GoTrue being down rather than a rejected request.
+fn is_outage(error: &ApiError) -> bool {
+    error.is_transient() && !matches!(error, ApiError::RateLimited { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            CircuitBreakerConfig::builder()
+                .minimum_calls(4)
+                .failure_rate_threshold(0.5)
+                .open_duration(Duration::from_millis(20))
+                .build(),
+        )
+    }
+
+    fn call(breaker: &CircuitBreaker, result: Result<(), &ApiError>) {
+        let permit = breaker.try_acquire().expect("circuit is open");
+        breaker.record(permit, result);
+    }
+
+    #[test]
+    fn opens_at_failure_rate_and_closes_after_probe() {
+        let breaker = breaker();
+        let outage = ApiError::UnknownHTTP(StatusCode::BAD_GATEWAY);
+
+        call(&breaker, Ok(()));
+        call(&breaker, Ok(()));
+        call(&breaker, Err(&outage));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        call(&breaker, Err(&outage));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_none());
+
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        let probe = breaker.try_acquire().expect("probe is let through");
+        assert!(breaker.try_acquire().is_none());
+
+        breaker.record(probe, Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn rejected_requests_are_no_failures() {
+        let breaker = breaker();
+        let rejected = ApiError::UnknownHTTP(StatusCode::BAD_REQUEST);
+
+        for _ in 0..10 {
+            call(&breaker, Err(&rejected));
+        }
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
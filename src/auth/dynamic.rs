@@ -0,0 +1,358 @@
+use crate::api::{IdTokenCredentials, ResendRequest, Settings, SignUpOptions, SignUpResponse};
+use crate::auth::circuit_breaker::CircuitBreaker;
+use crate::auth::ClientError;
+use crate::{
+    AccessToken, Auth, AuthTypes, DefaultAuthTypes, EmailOrPhone, LogoutScope, OAuthRequest,
+    OAuthResponse, RefreshToken, Session, SessionAuth, SsoRequest, User, UserAttributes,
+};
+use futures_util::future::BoxFuture;
+use std::future::Future;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Type-erased [`Auth`] implementation, so [`AuthState`](crate::middleware::AuthState) can hold
+/// an [`AuthService`](crate::AuthService) in production and e.g. a mock in tests.
+pub struct DynAuth<T: AuthTypes = DefaultAuthTypes> {
+    inner: Arc<dyn ErasedAuth<T>>,
+}
+
+impl<T: AuthTypes> DynAuth<T> {
+    pub fn new(auth: impl Auth<T>) -> Self {
+        Self {
+            inner: Arc::new(auth),
+        }
+    }
+}
+
+impl<T: AuthTypes> Clone for DynAuth<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Type-erased [`SessionAuth`] implementation returned by [`DynAuth`].
+pub struct DynSessionAuth<T: AuthTypes = DefaultAuthTypes> {
+    inner: Box<dyn ErasedSessionAuth<T>>,
+}
+
+/// Object-safe counterpart of [`Auth`], implemented for every [`Auth`] implementation.
+trait ErasedAuth<T: AuthTypes>: Send + Sync {
+    fn sign_up(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: String,
+        options: SignUpOptions<T>,
+    ) -> BoxFuture<'_, Result<SignUpResponse<T>, ClientError>>;
+
+    fn sign_in(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: String,
+        captcha_token: Option<String>,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>>;
+
+    fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>>;
+
+    fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>>;
+
+    fn resend(&self, request: ResendRequest) -> BoxFuture<'_, Result<(), ClientError>>;
+
+    fn settings(&self) -> BoxFuture<'_, Result<Settings, ClientError>>;
+
+    fn exchange_code_for_session<'a>(
+        &'a self,
+        code: &'a str,
+        csrf_token_b64: &'a str,
+    ) -> BoxFuture<'a, Result<Session<T>, ClientError>>;
+
+    fn create_oauth_url(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError>;
+
+    fn sign_in_with_sso(
+        &self,
+        req: SsoRequest,
+    ) -> BoxFuture<'_, Result<OAuthResponse, ClientError>>;
+
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker>;
+
+    fn with_token(&self, access_token: AccessToken) -> Box<dyn ErasedSessionAuth<T>>;
+
+    fn with_refresh_token(
+        &self,
+        access_token: AccessToken,
+        refresh_token: RefreshToken,
+    ) -> Box<dyn ErasedSessionAuth<T>>;
+}
+
+/// Object-safe counterpart of [`SessionAuth`].
+trait ErasedSessionAuth<T: AuthTypes>: Send + Sync {
+    fn logout(&self, scope: LogoutScope) -> BoxFuture<'_, Result<(), ClientError>>;
+
+    fn refresh(&mut self) -> BoxFuture<'_, Result<Session<T>, ClientError>>;
+
+    fn update_user(
+        &self,
+        attributes: UserAttributes<T>,
+    ) -> BoxFuture<'_, Result<User<T>, ClientError>>;
+
+    fn reauthenticate(&self) -> BoxFuture<'_, Result<(), ClientError>>;
+
+    fn link_identity(&self, req: OAuthRequest)
+        -> BoxFuture<'_, Result<OAuthResponse, ClientError>>;
+
+    fn unlink_identity(&self, identity_id: Uuid) -> BoxFuture<'_, Result<(), ClientError>>;
+}
+
+impl<T: AuthTypes, A: Auth<T>> ErasedAuth<T> for A {
+    fn sign_up(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: String,
+        options: SignUpOptions<T>,
+    ) -> BoxFuture<'_, Result<SignUpResponse<T>, ClientError>> {
+        Box::pin(Auth::sign_up(self, email_or_phone, password, options))
+    }
+
+    fn sign_in(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: String,
+        captcha_token: Option<String>,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>> {
+        Box::pin(Auth::sign_in(self, email_or_phone, password, captcha_token))
+    }
+
+    fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>> {
+        Box::pin(Auth::sign_in_with_id_token(self, credentials))
+    }
+
+    fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> BoxFuture<'_, Result<Session<T>, ClientError>> {
+        Box::pin(Auth::sign_in_anonymously(self, data, captcha_token))
+    }
+
+    fn resend(&self, request: ResendRequest) -> BoxFuture<'_, Result<(), ClientError>> {
+        Box::pin(Auth::resend(self, request))
+    }
+
+    fn settings(&self) -> BoxFuture<'_, Result<Settings, ClientError>> {
+        Box::pin(Auth::settings(self))
+    }
+
+    fn exchange_code_for_session<'a>(
+        &'a self,
+        code: &'a str,
+        csrf_token_b64: &'a str,
+    ) -> BoxFuture<'a, Result<Session<T>, ClientError>> {
+        Box::pin(Auth::exchange_code_for_session(self, code, csrf_token_b64))
+    }
+
+    fn create_oauth_url(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError> {
+        Auth::create_oauth_url(self, req)
+    }
+
+    fn sign_in_with_sso(
+        &self,
+        req: SsoRequest,
+    ) -> BoxFuture<'_, Result<OAuthResponse, ClientError>> {
+        Box::pin(Auth::sign_in_with_sso(self, req))
+    }
+
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        Auth::circuit_breaker(self)
+    }
+
+    fn with_token(&self, access_token: AccessToken) -> Box<dyn ErasedSessionAuth<T>> {
+        Box::new(SessionAdapter(Auth::with_token(self, access_token)))
+    }
+
+    fn with_refresh_token(
+        &self,
+        access_token: AccessToken,
+        refresh_token: RefreshToken,
+    ) -> Box<dyn ErasedSessionAuth<T>> {
+        Box::new(SessionAdapter(Auth::with_refresh_token(
+            self,
+            access_token,
+            refresh_token,
+        )))
+    }
+}
+
+/// Wraps a [`SessionAuth`] implementation, since a blanket impl of [`ErasedSessionAuth`] would
+/// conflict with the one of [`ErasedAuth`].
+struct SessionAdapter<S>(S);
+
+impl<T, S> ErasedSessionAuth<T> for SessionAdapter<S>
+where
+    T: AuthTypes,
+    S: SessionAuth<T> + Send + Sync,
+{
+    fn logout(&self, scope: LogoutScope) -> BoxFuture<'_, Result<(), ClientError>> {
+        Box::pin(self.0.logout(scope))
+    }
+
+    fn refresh(&mut self) -> BoxFuture<'_, Result<Session<T>, ClientError>> {
+        Box::pin(self.0.refresh())
+    }
+
+    fn update_user(
+        &self,
+        attributes: UserAttributes<T>,
+    ) -> BoxFuture<'_, Result<User<T>, ClientError>> {
+        Box::pin(self.0.update_user(attributes))
+    }
+
+    fn reauthenticate(&self) -> BoxFuture<'_, Result<(), ClientError>> {
+        Box::pin(self.0.reauthenticate())
+    }
+
+    fn link_identity(
+        &self,
+        req: OAuthRequest,
+    ) -> BoxFuture<'_, Result<OAuthResponse, ClientError>> {
+        Box::pin(self.0.link_identity(req))
+    }
+
+    fn unlink_identity(&self, identity_id: Uuid) -> BoxFuture<'_, Result<(), ClientError>> {
+        Box::pin(self.0.unlink_identity(identity_id))
+    }
+}
+
+impl<T: AuthTypes> Auth<T> for DynAuth<T> {
+    fn sign_up(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: impl AsRef<str> + Send,
+        options: SignUpOptions<T>,
+    ) -> impl Future<Output = Result<SignUpResponse<T>, ClientError>> + Send {
+        self.inner
+            .sign_up(email_or_phone, password.as_ref().to_string(), options)
+    }
+
+    fn sign_in(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: impl AsRef<str> + Send,
+        captcha_token: Option<String>,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send {
+        self.inner
+            .sign_in(email_or_phone, password.as_ref().to_string(), captcha_token)
+    }
+
+    fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send {
+        self.inner.sign_in_with_id_token(credentials)
+    }
+
+    fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send {
+        self.inner.sign_in_anonymously(data, captcha_token)
+    }
+
+    fn resend(
+        &self,
+        request: ResendRequest,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send {
+        self.inner.resend(request)
+    }
+
+    fn settings(&self) -> impl Future<Output = Result<Settings, ClientError>> + Send {
+        self.inner.settings()
+    }
+
+    async fn exchange_code_for_session(
+        &self,
+        code: &str,
+        csrf_token_b64: &str,
+    ) -> Result<Session<T>, ClientError> {
+        self.inner
+            .exchange_code_for_session(code, csrf_token_b64)
+            .await
+    }
+
+    fn create_oauth_url(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError> {
+        self.inner.create_oauth_url(req)
+    }
+
+    fn sign_in_with_sso(
+        &self,
+        req: SsoRequest,
+    ) -> impl Future<Output = Result<OAuthResponse, ClientError>> + Send {
+        self.inner.sign_in_with_sso(req)
+    }
+
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.inner.circuit_breaker()
+    }
+
+    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth<T> + Send + Sync + 'static {
+        DynSessionAuth {
+            inner: self.inner.with_token(access_token),
+        }
+    }
+
+    fn with_refresh_token(
+        &self,
+        access_token: AccessToken,
+        refresh_token: RefreshToken,
+    ) -> impl SessionAuth<T> + Send + Sync + 'static {
+        DynSessionAuth {
+            inner: self.inner.with_refresh_token(access_token, refresh_token),
+        }
+    }
+}
+
+impl<T: AuthTypes> SessionAuth<T> for DynSessionAuth<T> {
+    fn logout(&self, scope: LogoutScope) -> impl Future<Output = Result<(), ClientError>> + Send {
+        self.inner.logout(scope)
+    }
+
+    fn refresh(&mut self) -> impl Future<Output = Result<Session<T>, ClientError>> + Send {
+        self.inner.refresh()
+    }
+
+    fn update_user(
+        &self,
+        attributes: UserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send {
+        self.inner.update_user(attributes)
+    }
+
+    fn reauthenticate(&self) -> impl Future<Output = Result<(), ClientError>> + Send {
+        self.inner.reauthenticate()
+    }
+
+    fn link_identity(
+        &self,
+        req: OAuthRequest,
+    ) -> impl Future<Output = Result<OAuthResponse, ClientError>> + Send {
+        self.inner.link_identity(req)
+    }
+
+    fn unlink_identity(
+        &self,
+        identity_id: Uuid,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send {
+        self.inner.unlink_identity(identity_id)
+    }
+}
@@ -1,55 +1,213 @@
-use crate::api::SignUpResponse;
-use crate::{AccessToken, EmailOrPhone, OAuthRequest, OAuthResponse, RefreshToken, Session, User};
+use crate::api::{
+    AdminUserAttributes, GenerateLinkRequest, GenerateLinkResponse, IdTokenCredentials,
+    InviteUserRequest, ListUsersQuery, ResendRequest, Settings, SignUpOptions, SignUpResponse,
+    SsoProvider, SsoProviderAttributes,
+};
+use crate::{
+    AccessToken, AuthTypes, DefaultAuthTypes, EmailOrPhone, LogoutScope, OAuthRequest,
+    OAuthResponse, RefreshToken, Session, SsoRequest, User, UserAttributes, UserList,
+};
+use circuit_breaker::CircuitBreaker;
+use futures_util::Stream;
 use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
+use uuid::Uuid;
 
 pub mod api;
+pub mod circuit_breaker;
+pub mod dynamic;
 pub mod service;
 pub mod types;
 
-pub trait Auth: Clone + Send + Sync + 'static {
+pub trait Auth<T: AuthTypes = DefaultAuthTypes>: Clone + Send + Sync + 'static {
     fn sign_up(
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Send,
-    ) -> impl Future<Output = Result<SignUpResponse, ClientError>> + Send;
+        options: SignUpOptions<T>,
+    ) -> impl Future<Output = Result<SignUpResponse<T>, ClientError>> + Send;
 
     fn sign_in(
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Send,
-    ) -> impl Future<Output = Result<Session, ClientError>> + Send;
+        captcha_token: Option<String>,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send;
+
+    /// Signs in with an OIDC ID token obtained natively from the identity provider.
+    fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send;
+
+    /// Signs in a new anonymous user. The user can later be converted into a permanent user
+    /// with [`SessionAuth::update_user`] or [`SessionAuth::link_identity`], keeping its id.
+    fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send;
+
+    /// Resends a signup confirmation, email change or phone OTP, e.g. when the user lost the
+    /// confirmation mail.
+    fn resend(
+        &self,
+        request: ResendRequest,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send;
+
+    /// Fetches the public GoTrue settings. [`AuthState::settings`](crate::middleware::AuthState::settings)
+    /// caches them.
+    fn settings(&self) -> impl Future<Output = Result<Settings, ClientError>> + Send;
 
     fn exchange_code_for_session(
         &self,
         code: &str,
         csrf_token_b64: &str,
-    ) -> impl Future<Output = Result<Session, ClientError>> + Send;
+    ) -> impl Future<Output = Result<Session<T>, ClientError>> + Send;
 
     // TODO: move to axum?
     fn create_oauth_url(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError>;
 
-    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth;
+    /// Creates a PKCE authorize URL for enterprise SSO (SAML 2.0). The flow completes like
+    /// [`Auth::create_oauth_url`] with [`Auth::exchange_code_for_session`].
+    fn sign_in_with_sso(
+        &self,
+        req: SsoRequest,
+    ) -> impl Future<Output = Result<OAuthResponse, ClientError>> + Send;
+
+    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth<T> + Send + Sync + 'static;
 
     fn with_refresh_token(
         &self,
         access_token: AccessToken,
         refresh_token: RefreshToken,
-    ) -> impl SessionAuth;
+    ) -> impl SessionAuth<T> + Send + Sync + 'static;
+
+    /// Returns the circuit breaker the calls go through, if any.
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        None
+    }
 }
 
-pub trait SessionAuth {
-    fn logout(&self) -> impl Future<Output = Result<(), ClientError>> + Send;
+pub trait SessionAuth<T: AuthTypes = DefaultAuthTypes> {
+    fn logout(&self, scope: LogoutScope) -> impl Future<Output = Result<(), ClientError>> + Send;
+
+    fn refresh(&mut self) -> impl Future<Output = Result<Session<T>, ClientError>> + Send;
+
+    fn update_user(
+        &self,
+        attributes: UserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    /// Sends a nonce to the user, which has to be passed as [`UserAttributes::nonce`] to update
+    /// the password when secure password change is enabled.
+    fn reauthenticate(&self) -> impl Future<Output = Result<(), ClientError>> + Send;
+
+    /// Creates a PKCE authorize URL to link an additional identity to the signed in user.
+    /// The flow completes like [`Auth::create_oauth_url`] with
+    /// [`Auth::exchange_code_for_session`].
+    fn link_identity(
+        &self,
+        req: OAuthRequest,
+    ) -> impl Future<Output = Result<OAuthResponse, ClientError>> + Send;
+
+    fn unlink_identity(
+        &self,
+        identity_id: Uuid,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send;
+}
+
+/// User management through the GoTrue admin API. Requires the `service_role` key.
+pub trait AdminAuth<T: AuthTypes = DefaultAuthTypes> {
+    fn create_user(
+        &self,
+        attributes: AdminUserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    fn get_user(&self, user_id: Uuid) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    fn update_user(
+        &self,
+        user_id: Uuid,
+        attributes: AdminUserAttributes<T>,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    fn delete_user(
+        &self,
+        user_id: Uuid,
+        soft_delete: bool,
+    ) -> impl Future<Output = Result<(), ClientError>> + Send;
+
+    /// Bans a user for the given duration, rounded down to whole seconds.
+    fn ban_user(
+        &self,
+        user_id: Uuid,
+        duration: Duration,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    fn unban_user(
+        &self,
+        user_id: Uuid,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
+
+    fn invite_user(
+        &self,
+        request: InviteUserRequest<T>,
+    ) -> impl Future<Output = Result<User<T>, ClientError>> + Send;
 
-    fn list_users(&self) -> impl Future<Output = Result<Vec<User>, ClientError>> + Send;
+    fn generate_link(
+        &self,
+        request: GenerateLinkRequest<T>,
+    ) -> impl Future<Output = Result<GenerateLinkResponse<T>, ClientError>> + Send;
+
+    /// Lists a single page of users. Pages start at 1.
+    fn list_users(
+        &self,
+        query: ListUsersQuery,
+    ) -> impl Future<Output = Result<UserList<T>, ClientError>> + Send;
 
-    fn refresh(&mut self) -> impl Future<Output = Result<Session, ClientError>> + Send;
+    fn list_sso_providers(
+        &self,
+    ) -> impl Future<Output = Result<Vec<SsoProvider>, ClientError>> + Send;
+
+    fn create_sso_provider(
+        &self,
+        attributes: SsoProviderAttributes,
+    ) -> impl Future<Output = Result<SsoProvider, ClientError>> + Send;
+
+    fn get_sso_provider(
+        &self,
+        provider_id: Uuid,
+    ) -> impl Future<Output = Result<SsoProvider, ClientError>> + Send;
+
+    fn update_sso_provider(
+        &self,
+        provider_id: Uuid,
+        attributes: SsoProviderAttributes,
+    ) -> impl Future<Output = Result<SsoProvider, ClientError>> + Send;
+
+    fn delete_sso_provider(
+        &self,
+        provider_id: Uuid,
+    ) -> impl Future<Output = Result<SsoProvider, ClientError>> + Send;
+
+    /// Streams all users matching `query`, starting at `query.page`. Pages are fetched lazily,
+    /// following the `Link` header GoTrue returns until the last page is reached.
+    fn stream_users(
+        &self,
+        query: ListUsersQuery,
+    ) -> impl Stream<Item = Result<User<T>, ClientError>> + Send;
 }
 
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error("User already signed up")]
     AlreadySignedUp,
+    #[error("Sign-ups are disabled")]
+    SignUpDisabled,
+    #[error("Invalid sign-up: {0}")]
+    InvalidSignUp(String),
     #[error("Wrong credentials")]
     WrongCredentials,
     #[error("User not found")]
@@ -60,6 +218,32 @@ pub enum ClientError {
     MissingRefreshToken,
     #[error("Wrong token")]
     WrongToken,
+    #[error("Identity not found")]
+    IdentityNotFound,
+    #[error("Identity is already linked to a user")]
+    IdentityAlreadyExists,
+    #[error("The only identity of a user can not be unlinked")]
+    SingleIdentityNotDeletable,
+    #[error("Manual identity linking is disabled")]
+    ManualLinkingDisabled,
+    #[error("Anonymous sign-ins are disabled")]
+    AnonymousSignInDisabled,
+    #[error("Password is too weak")]
+    WeakPassword,
+    #[error("SSO provider not found")]
+    SsoProviderNotFound,
+    #[error("Too many emails sent, try again later")]
+    EmailRateLimited,
+    #[error("Too many SMS sent, try again later")]
+    SmsRateLimited,
+    #[error("Too many requests, retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("Reauthentication required, request a nonce first")]
+    ReauthenticationNeeded,
+    #[error("Reauthentication nonce is invalid or expired")]
+    ReauthenticationNotValid,
+    #[error("GoTrue is unavailable, the circuit breaker is open")]
+    Unavailable,
     #[error("GoTrue Internal error")]
     InternalError,
 }
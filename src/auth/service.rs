@@ -1,45 +1,184 @@
-use crate::api::{Api, ApiError, ApiErrorCode, SignUpResponse};
+use crate::api::{
+    AdminUserAttributes, Api, ApiError, ApiErrorCode, GenerateLinkRequest, GenerateLinkResponse,
+    IdTokenCredentials, InviteUserRequest, ListUsersQuery, ResendRequest, RetryPolicy, Settings,
+    SignUpOptions, SignUpResponse, SsoProvider, SsoProviderAttributes,
+};
 use crate::auth::api::ApiClient;
+use crate::auth::api::{ClientBuildError, DynTransport, DEFAULT_TIMEOUT};
+use crate::auth::circuit_breaker::CircuitBreaker;
 use crate::auth::ClientError;
 use crate::{
-    AccessToken, Auth, EmailOrPhone, OAuthRequest, OAuthResponse, RefreshToken, Session,
-    SessionAuth, User,
+    AccessToken, AdminAuth, Auth, AuthTypes, DefaultAuthTypes, EmailOrPhone, LogoutScope,
+    OAuthRequest, OAuthResponse, RefreshToken, Session, SessionAuth, SsoRequest, User,
+    UserAttributes, UserList,
 };
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bon::bon;
+use futures_util::{stream, Stream, TryStreamExt};
 use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
-use reqwest::Url;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::error;
+use url::Url;
+use uuid::Uuid;
 
-#[derive(Clone)]
-pub struct AuthService {
-    api: Arc<ApiClient>,
+pub struct AuthService<T: AuthTypes = DefaultAuthTypes> {
+    api: Arc<ApiClient<T>>,
+    circuit_breaker: Option<CircuitBreaker>,
 }
 
-impl AuthService {
+impl<T: AuthTypes> Clone for AuthService<T> {
+    fn clone(&self) -> Self {
+        Self {
+            api: self.api.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+        }
+    }
+}
+
+#[bon]
+impl<T: AuthTypes> AuthService<T> {
+    /// Creates a service, returning an error instead of panicking on an invalid API key. The
+    /// options are the same as for [`ApiClient::builder`].
+    #[builder(
+        start_fn(name = builder, vis = "pub"),
+        builder_type(vis = "pub"),
+        finish_fn(name = build, vis = "pub")
+    )]
+    fn from_builder(
+        url: Url,
+        #[builder(into)] api_key: String,
+        #[builder(into)] transport: Option<DynTransport>,
+        #[builder(default = DEFAULT_TIMEOUT)] timeout: Duration,
+        #[builder(into)] user_agent: Option<String>,
+        #[builder(default)] headers: HeaderMap,
+        #[builder(default)] retry_policy: RetryPolicy,
+        circuit_breaker: Option<CircuitBreaker>,
+    ) -> Result<Self, ClientBuildError> {
+        let api = ApiClient::builder()
+            .url(url)
+            .api_key(api_key)
+            .maybe_transport(transport)
+            .timeout(timeout)
+            .maybe_user_agent(user_agent)
+            .headers(headers)
+            .retry_policy(retry_policy)
+            .build()?;
+
+        Ok(Self {
+            api: Arc::new(api),
+            circuit_breaker,
+        })
+    }
+}
+
+impl<T: AuthTypes> From<ApiClient<T>> for AuthService<T> {
+    fn from(api: ApiClient<T>) -> Self {
+        Self {
+            api: Arc::new(api),
+            circuit_breaker: None,
+        }
+    }
+}
+
+impl<T: AuthTypes> AuthService<T> {
+    #[cfg(feature = "reqwest")]
     pub fn new(url: Url, api_key: &str) -> Self {
-        Self::new_with_timeout(url, api_key, Duration::from_secs(2))
+        Self::new_with_timeout(url, api_key, DEFAULT_TIMEOUT)
     }
 
+    #[cfg(feature = "reqwest")]
     pub fn new_with_timeout(url: Url, api_key: &str, timeout: Duration) -> Self {
+        Self::from(ApiClient::new(url, timeout, api_key))
+    }
+
+    /// Sets the retry policy of idempotent requests, defaults to [`RetryPolicy::default`].
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            api: Arc::new((*self.api).clone().with_retry_policy(retry_policy)),
+            circuit_breaker: self.circuit_breaker,
+        }
+    }
+
+    /// Fails calls fast with [`ClientError::Unavailable`] while `circuit_breaker` is open.
+    pub fn with_circuit_breaker(self, circuit_breaker: CircuitBreaker) -> Self {
         Self {
-            api: Arc::new(ApiClient::new(url, timeout, api_key)),
+            circuit_breaker: Some(circuit_breaker),
+            ..self
+        }
+    }
+
+    /// Runs a GoTrue request through the circuit breaker, failing fast while it is open.
+    async fn call<R>(
+        &self,
+        request: impl Future<Output = Result<R, ApiError>>,
+    ) -> Result<Result<R, ApiError>, ClientError> {
+        let Some(circuit_breaker) = &self.circuit_breaker else {
+            return Ok(request.await);
+        };
+        let Some(permit) = circuit_breaker.try_acquire() else {
+            return Err(ClientError::Unavailable);
+        };
+
+        let result = request.await;
+        circuit_breaker.record(permit, result.as_ref().map(|_| ()));
+
+        Ok(result)
+    }
+
+    /// Creates an admin client authenticated with the `service_role` key.
+    ///
+    /// The `service_role` key bypasses all security checks and must never be exposed to users.
+    pub fn with_service_role_key(
+        &self,
+        service_role_key: impl Into<String>,
+    ) -> AdminAuthService<T> {
+        AdminAuthService {
+            auth: self.clone(),
+            service_role_key: AccessToken::from(service_role_key.into()),
         }
     }
 }
 
-impl Auth for AuthService {
+impl<T: AuthTypes> Auth<T> for AuthService<T> {
     async fn sign_up(
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Send,
-    ) -> Result<SignUpResponse, ClientError> {
-        match self.api.sign_up(email_or_phone, password).await {
+        options: SignUpOptions<T>,
+    ) -> Result<SignUpResponse<T>, ClientError> {
+        match self
+            .call(self.api.sign_up(email_or_phone, password, &options))
+            .await?
+        {
             Ok(session) => Ok(session),
-            Err(ApiError::Request(StatusCode::UNPROCESSABLE_ENTITY, _, _)) => {
-                Err(ClientError::AlreadySignedUp)
+            Err(ApiError::Request(_, ApiErrorCode::WeakPassword, _)) => {
+                Err(ClientError::WeakPassword)
+            }
+            Err(ApiError::Request(
+                _,
+                ApiErrorCode::UserAlreadyExists | ApiErrorCode::EmailExists,
+                _,
+            )) => Err(ClientError::AlreadySignedUp),
+            Err(ApiError::Request(
+                _,
+                ApiErrorCode::SignupDisabled
+                | ApiErrorCode::EmailProviderDisabled
+                | ApiErrorCode::PhoneProviderDisabled,
+                _,
+            )) => Err(ClientError::SignUpDisabled),
+            Err(ApiError::Request(
+                _,
+                ApiErrorCode::EmailAddressInvalid | ApiErrorCode::ValidationFailed,
+                msg,
+            ))
+            | Err(ApiError::Request(StatusCode::UNPROCESSABLE_ENTITY, _, msg)) => {
+                Err(ClientError::InvalidSignUp(msg))
+            }
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
             }
             Err(e) => {
                 error!("Error signing up: {:?}", e);
@@ -52,12 +191,22 @@ impl Auth for AuthService {
         &self,
         email_or_phone: EmailOrPhone,
         password: impl AsRef<str> + Send,
-    ) -> Result<Session, ClientError> {
-        match self.api.sign_in(email_or_phone, password).await {
+        captcha_token: Option<String>,
+    ) -> Result<Session<T>, ClientError> {
+        match self
+            .call(
+                self.api
+                    .sign_in(email_or_phone, password, captcha_token.as_deref()),
+            )
+            .await?
+        {
             Ok(session) => Ok(session),
             Err(ApiError::Request(StatusCode::BAD_REQUEST, _, _)) => {
                 Err(ClientError::WrongCredentials)
             }
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
+            }
             Err(e) => {
                 error!("Error signing in: {:?}", e);
                 Err(ClientError::InternalError)
@@ -65,11 +214,93 @@ impl Auth for AuthService {
         }
     }
 
+    async fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> Result<Session<T>, ClientError> {
+        match self
+            .call(self.api.sign_in_with_id_token(&credentials))
+            .await?
+        {
+            Ok(session) => Ok(session),
+            Err(ApiError::OAuth(StatusCode::BAD_REQUEST, _, _)) => Err(ClientError::WrongToken),
+            Err(ApiError::Request(StatusCode::BAD_REQUEST, _, _)) => {
+                Err(ClientError::WrongCredentials)
+            }
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
+            }
+            Err(e) => {
+                error!("Error signing in with ID token: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
+
+    async fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> Result<Session<T>, ClientError> {
+        match self
+            .call(
+                self.api
+                    .sign_in_anonymously(data.as_ref(), captcha_token.as_deref()),
+            )
+            .await?
+        {
+            Ok(session) => Ok(session),
+            Err(ApiError::Request(
+                _,
+                ApiErrorCode::AnonymousProviderDisabled | ApiErrorCode::SignupDisabled,
+                _,
+            )) => Err(ClientError::AnonymousSignInDisabled),
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
+            }
+            Err(e) => {
+                error!("Error signing in anonymously: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
+
+    async fn resend(&self, request: ResendRequest) -> Result<(), ClientError> {
+        match self.call(self.api.resend(&request)).await? {
+            Ok(()) => Ok(()),
+            Err(ApiError::RateLimited {
+                code: ApiErrorCode::OverEmailSendRateLimit,
+                ..
+            }) => Err(ClientError::EmailRateLimited),
+            Err(ApiError::RateLimited {
+                code: ApiErrorCode::OverSmsSendRateLimit,
+                ..
+            }) => Err(ClientError::SmsRateLimited),
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
+            }
+            Err(e) => {
+                error!("Error resending: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
+
+    async fn settings(&self) -> Result<Settings, ClientError> {
+        match self.call(self.api.settings()).await? {
+            Ok(settings) => Ok(settings),
+            Err(e) => {
+                error!("Error fetching settings: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
+
     async fn exchange_code_for_session(
         &self,
         code: &str,
         csrf_token_b64: &str,
-    ) -> Result<Session, ClientError> {
+    ) -> Result<Session<T>, ClientError> {
         let csrf_token = BASE64_STANDARD
             .decode(csrf_token_b64)
             .map_err(|_| ClientError::WrongToken)?;
@@ -78,7 +309,10 @@ impl Auth for AuthService {
             String::from_utf8(csrf_token).map_err(|_| ClientError::WrongToken)?,
         );
 
-        match self.api.exchange_code_for_session(code, &verifier).await {
+        match self
+            .call(self.api.exchange_code_for_session(code, &verifier))
+            .await?
+        {
             Ok(session) => Ok(session),
             Err(e) => {
                 error!("Error exchanging code for session: {:?}", e);
@@ -91,15 +325,35 @@ impl Auth for AuthService {
         let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
         let url = self.api.create_pkce_oauth_url(req, pkce_challenge);
-        let csrf_token = BASE64_STANDARD.encode(pkce_verifier.secret());
 
-        Ok(OAuthResponse {
-            supabase_url: url.to_string(),
-            csrf_token,
-        })
+        Ok(OAuthResponse::new(url, &pkce_verifier))
+    }
+
+    async fn sign_in_with_sso(&self, req: SsoRequest) -> Result<OAuthResponse, ClientError> {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        match self
+            .call(self.api.create_pkce_sso_url(req, pkce_challenge))
+            .await?
+        {
+            Ok(url) => Ok(OAuthResponse::new(url, &pkce_verifier)),
+            Err(ApiError::Request(
+                _,
+                ApiErrorCode::SsoProviderNotFound | ApiErrorCode::SamlProviderDisabled,
+                _,
+            )) => Err(ClientError::SsoProviderNotFound),
+            Err(e) => {
+                error!("Error signing in with SSO: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
+
+    fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
     }
 
-    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth {
+    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth<T> + Send + Sync + 'static {
         SessionAuthService::with_token(self.clone(), access_token)
     }
 
@@ -107,26 +361,35 @@ impl Auth for AuthService {
         &self,
         access_token: AccessToken,
         refresh_token: RefreshToken,
-    ) -> impl SessionAuth {
+    ) -> impl SessionAuth<T> + Send + Sync + 'static {
         SessionAuthService::with_refresh_token(self.clone(), access_token, refresh_token)
     }
 }
 
-#[derive(Clone)]
-pub struct SessionAuthService {
-    auth: AuthService,
+pub struct SessionAuthService<T: AuthTypes = DefaultAuthTypes> {
+    auth: AuthService<T>,
     access_token: AccessToken,
     refresh_token: Option<RefreshToken>,
 }
 
-impl AsRef<AuthService> for SessionAuthService {
-    fn as_ref(&self) -> &AuthService {
+impl<T: AuthTypes> Clone for SessionAuthService<T> {
+    fn clone(&self) -> Self {
+        Self {
+            auth: self.auth.clone(),
+            access_token: self.access_token.clone(),
+            refresh_token: self.refresh_token.clone(),
+        }
+    }
+}
+
+impl<T: AuthTypes> AsRef<AuthService<T>> for SessionAuthService<T> {
+    fn as_ref(&self) -> &AuthService<T> {
         &self.auth
     }
 }
 
-impl SessionAuthService {
-    fn with_token(auth: AuthService, access_token: AccessToken) -> Self {
+impl<T: AuthTypes> SessionAuthService<T> {
+    fn with_token(auth: AuthService<T>, access_token: AccessToken) -> Self {
         Self {
             auth,
             access_token,
@@ -135,7 +398,7 @@ impl SessionAuthService {
     }
 
     fn with_refresh_token(
-        auth: AuthService,
+        auth: AuthService<T>,
         access_token: AccessToken,
         refresh_token: RefreshToken,
     ) -> Self {
@@ -145,11 +408,37 @@ impl SessionAuthService {
             refresh_token: Some(refresh_token),
         }
     }
+
+    async fn refresh_session(&self) -> Result<Session<T>, ClientError> {
+        let refresh_token = match self.refresh_token {
+            Some(ref refresh_token) => refresh_token,
+            None => return Err(ClientError::MissingRefreshToken),
+        };
+
+        match self
+            .auth
+            .call(self.auth.api.refresh_access_token(refresh_token))
+            .await?
+        {
+            Ok(session) => Ok(session),
+            Err(ApiError::RateLimited { retry_after, .. }) => {
+                Err(ClientError::RateLimited { retry_after })
+            }
+            Err(e) => {
+                error!("Error refreshing token: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
 }
 
-impl SessionAuth for SessionAuthService {
-    async fn logout(&self) -> Result<(), ClientError> {
-        match self.auth.api.logout(&self.access_token).await {
+impl<T: AuthTypes> SessionAuth<T> for SessionAuthService<T> {
+    async fn logout(&self, scope: LogoutScope) -> Result<(), ClientError> {
+        match self
+            .auth
+            .call(self.auth.api.logout(&self.access_token, scope))
+            .await?
+        {
             Ok(_) => Ok(()),
             Err(e) => {
                 error!("Error logging out: {:?}", e);
@@ -158,35 +447,340 @@ impl SessionAuth for SessionAuthService {
         }
     }
 
-    async fn list_users(&self) -> Result<Vec<User>, ClientError> {
-        match self.auth.api.list_users(&self.access_token).await {
-            Ok(users) => Ok(users.users),
-            Err(ApiError::Request(_, ApiErrorCode::InvalidCredentials, _)) => {
-                Err(ClientError::WrongCredentials)
+    async fn update_user(&self, attributes: UserAttributes<T>) -> Result<User<T>, ClientError> {
+        match self
+            .auth
+            .call(self.auth.api.update_user(&self.access_token, &attributes))
+            .await?
+        {
+            Ok(user) => Ok(user),
+            Err(ApiError::Request(_, ApiErrorCode::EmailExists | ApiErrorCode::PhoneExists, _)) => {
+                Err(ClientError::AlreadySignedUp)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::WeakPassword, _)) => {
+                Err(ClientError::WeakPassword)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::ReauthenticationNeeded, _)) => {
+                Err(ClientError::ReauthenticationNeeded)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::ReauthenticationNotValid, _)) => {
+                Err(ClientError::ReauthenticationNotValid)
             }
             Err(e) => {
-                error!("Error listing users: {:?}", e);
+                error!("Error updating user: {:?}", e);
                 Err(ClientError::InternalError)
             }
         }
     }
 
-    async fn refresh(&mut self) -> Result<Session, ClientError> {
-        let refresh_token = match self.refresh_token {
-            Some(ref refresh_token) => refresh_token,
-            None => return Err(ClientError::MissingRefreshToken),
-        };
+    async fn reauthenticate(&self) -> Result<(), ClientError> {
+        match self
+            .auth
+            .call(self.auth.api.reauthenticate(&self.access_token))
+            .await?
+        {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Error reauthenticating: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
 
-        let session = match self.auth.api.refresh_access_token(refresh_token).await {
-            Ok(session) => session,
+    async fn link_identity(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError> {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        match self
+            .auth
+            .call(self.auth.api.create_pkce_link_identity_url(
+                &self.access_token,
+                req,
+                pkce_challenge,
+            ))
+            .await?
+        {
+            Ok(url) => Ok(OAuthResponse::new(url, &pkce_verifier)),
+            Err(ApiError::Request(_, ApiErrorCode::ManualLinkingDisabled, _)) => {
+                Err(ClientError::ManualLinkingDisabled)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::IdentityAlreadyExists, _)) => {
+                Err(ClientError::IdentityAlreadyExists)
+            }
             Err(e) => {
-                error!("Error refreshing token: {:?}", e);
-                return Err(ClientError::InternalError);
+                error!("Error linking identity: {:?}", e);
+                Err(ClientError::InternalError)
             }
-        };
+        }
+    }
+
+    async fn unlink_identity(&self, identity_id: Uuid) -> Result<(), ClientError> {
+        match self
+            .auth
+            .call(
+                self.auth
+                    .api
+                    .unlink_identity(&self.access_token, identity_id),
+            )
+            .await?
+        {
+            Ok(()) => Ok(()),
+            Err(ApiError::Request(_, ApiErrorCode::IdentityNotFound, _)) => {
+                Err(ClientError::IdentityNotFound)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::SingleIdentityNotDeletable, _)) => {
+                Err(ClientError::SingleIdentityNotDeletable)
+            }
+            Err(ApiError::Request(_, ApiErrorCode::ManualLinkingDisabled, _)) => {
+                Err(ClientError::ManualLinkingDisabled)
+            }
+            Err(e) => {
+                error!("Error unlinking identity: {:?}", e);
+                Err(ClientError::InternalError)
+            }
+        }
+    }
 
+    async fn refresh(&mut self) -> Result<Session<T>, ClientError> {
+        let result = self.refresh_session().await;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_refresh(&result);
+
+        let session = result?;
         self.access_token = session.access_token.clone();
         self.refresh_token = Some(session.refresh_token.clone());
         Ok(session)
     }
 }
+
+pub struct AdminAuthService<T: AuthTypes = DefaultAuthTypes> {
+    auth: AuthService<T>,
+    service_role_key: AccessToken,
+}
+
+impl<T: AuthTypes> Clone for AdminAuthService<T> {
+    fn clone(&self) -> Self {
+        Self {
+            auth: self.auth.clone(),
+            service_role_key: self.service_role_key.clone(),
+        }
+    }
+}
+
+impl<T: AuthTypes> AdminAuthService<T> {
+    fn map_error(action: &str, error: ApiError) -> ClientError {
+        match error {
+            ApiError::Request(_, ApiErrorCode::SsoProviderNotFound, _) => {
+                ClientError::SsoProviderNotFound
+            }
+            ApiError::Request(StatusCode::NOT_FOUND, _, _)
+            | ApiError::Request(_, ApiErrorCode::UserNotFound, _) => ClientError::UserNotFound,
+            ApiError::Request(
+                _,
+                ApiErrorCode::UserAlreadyExists
+                | ApiErrorCode::EmailExists
+                | ApiErrorCode::PhoneExists,
+                _,
+            ) => ClientError::AlreadySignedUp,
+            ApiError::Request(_, ApiErrorCode::InvalidCredentials, _) => {
+                ClientError::WrongCredentials
+            }
+            ApiError::Request(
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+                ApiErrorCode::NotAdmin | ApiErrorCode::BadJwt,
+                _,
+            ) => ClientError::NotAuthenticated,
+            e => {
+                error!("Error {action}: {:?}", e);
+                ClientError::InternalError
+            }
+        }
+    }
+}
+
+impl<T: AuthTypes> AdminAuth<T> for AdminAuthService<T> {
+    async fn create_user(
+        &self,
+        attributes: AdminUserAttributes<T>,
+    ) -> Result<User<T>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_create_user(&self.service_role_key, &attributes),
+            )
+            .await?
+            .map_err(|e| Self::map_error("creating user", e))
+    }
+
+    async fn get_user(&self, user_id: Uuid) -> Result<User<T>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_get_user(&self.service_role_key, user_id),
+            )
+            .await?
+            .map_err(|e| Self::map_error("getting user", e))
+    }
+
+    async fn update_user(
+        &self,
+        user_id: Uuid,
+        attributes: AdminUserAttributes<T>,
+    ) -> Result<User<T>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_update_user(&self.service_role_key, user_id, &attributes),
+            )
+            .await?
+            .map_err(|e| Self::map_error("updating user", e))
+    }
+
+    async fn delete_user(&self, user_id: Uuid, soft_delete: bool) -> Result<(), ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_delete_user(&self.service_role_key, user_id, soft_delete),
+            )
+            .await?
+            .map_err(|e| Self::map_error("deleting user", e))
+    }
+
+    async fn ban_user(&self, user_id: Uuid, duration: Duration) -> Result<User<T>, ClientError> {
+        let attributes = AdminUserAttributes::builder()
+            .ban_duration(format!("{}s", duration.as_secs()))
+            .build();
+
+        self.update_user(user_id, attributes).await
+    }
+
+    async fn unban_user(&self, user_id: Uuid) -> Result<User<T>, ClientError> {
+        let attributes = AdminUserAttributes::builder().ban_duration("none").build();
+
+        self.update_user(user_id, attributes).await
+    }
+
+    async fn invite_user(&self, request: InviteUserRequest<T>) -> Result<User<T>, ClientError> {
+        self.auth
+            .call(self.auth.api.invite_user(&self.service_role_key, &request))
+            .await?
+            .map_err(|e| Self::map_error("inviting user", e))
+    }
+
+    async fn generate_link(
+        &self,
+        request: GenerateLinkRequest<T>,
+    ) -> Result<GenerateLinkResponse<T>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .generate_link(&self.service_role_key, &request),
+            )
+            .await?
+            .map_err(|e| Self::map_error("generating link", e))
+    }
+
+    async fn list_users(&self, query: ListUsersQuery) -> Result<UserList<T>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .list_users_query(&self.service_role_key, &query),
+            )
+            .await?
+            .map_err(|e| Self::map_error("listing users", e))
+    }
+
+    async fn list_sso_providers(&self) -> Result<Vec<SsoProvider>, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_list_sso_providers(&self.service_role_key),
+            )
+            .await?
+            .map(|providers| providers.items)
+            .map_err(|e| Self::map_error("listing SSO providers", e))
+    }
+
+    async fn create_sso_provider(
+        &self,
+        attributes: SsoProviderAttributes,
+    ) -> Result<SsoProvider, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_create_sso_provider(&self.service_role_key, &attributes),
+            )
+            .await?
+            .map_err(|e| Self::map_error("creating SSO provider", e))
+    }
+
+    async fn get_sso_provider(&self, provider_id: Uuid) -> Result<SsoProvider, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_get_sso_provider(&self.service_role_key, provider_id),
+            )
+            .await?
+            .map_err(|e| Self::map_error("getting SSO provider", e))
+    }
+
+    async fn update_sso_provider(
+        &self,
+        provider_id: Uuid,
+        attributes: SsoProviderAttributes,
+    ) -> Result<SsoProvider, ClientError> {
+        self.auth
+            .call(self.auth.api.admin_update_sso_provider(
+                &self.service_role_key,
+                provider_id,
+                &attributes,
+            ))
+            .await?
+            .map_err(|e| Self::map_error("updating SSO provider", e))
+    }
+
+    async fn delete_sso_provider(&self, provider_id: Uuid) -> Result<SsoProvider, ClientError> {
+        self.auth
+            .call(
+                self.auth
+                    .api
+                    .admin_delete_sso_provider(&self.service_role_key, provider_id),
+            )
+            .await?
+            .map_err(|e| Self::map_error("deleting SSO provider", e))
+    }
+
+    fn stream_users(
+        &self,
+        query: ListUsersQuery,
+    ) -> impl Stream<Item = Result<User<T>, ClientError>> + Send {
+        let admin = self.clone();
+
+        stream::try_unfold(Some(query), move |query| {
+            let admin = admin.clone();
+            async move {
+                let Some(query) = query else {
+                    return Ok(None);
+                };
+
+                let users = admin.list_users(query.clone()).await?;
+                let next = users.next_page.map(|page| ListUsersQuery {
+                    page: Some(page),
+                    ..query
+                });
+
+                Ok(Some((stream::iter(users.users.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+}
@@ -1,8 +1,15 @@
-use serde::Deserialize;
+use crate::middleware::AppMetadata;
+use crate::{AuthTypes, DefaultAuthTypes};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bon::Builder;
+use oauth2::PkceCodeVerifier;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Formatter};
 use time::OffsetDateTime;
+use url::Url;
+use uuid::Uuid;
 
-#[derive(Clone, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct AccessToken(String);
 
 impl Debug for AccessToken {
@@ -29,7 +36,7 @@ impl From<AccessToken> for String {
     }
 }
 
-#[derive(Clone, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct RefreshToken(String);
 
 impl Debug for RefreshToken {
@@ -56,18 +63,21 @@ impl From<RefreshToken> for String {
     }
 }
 
-#[derive(Clone, Deserialize, PartialEq, Eq)]
-pub struct Session {
+/// A signed in session. The [`User`] is typed through the [`AuthTypes`], like the users returned
+/// by [`SessionAuth::update_user`](crate::SessionAuth::update_user).
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Session<T: AuthTypes = DefaultAuthTypes> {
     pub access_token: AccessToken,
     pub token_type: String,
     pub expires_in: i32,
     #[serde(with = "time::serde::timestamp")]
     pub expires_at: OffsetDateTime,
     pub refresh_token: RefreshToken,
-    pub user: User,
+    pub user: User<T>,
 }
 
-impl Debug for Session {
+impl<T: AuthTypes> Debug for Session<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Session")
             .field("access_token", &"[redacted]")
@@ -80,35 +90,402 @@ impl Debug for Session {
     }
 }
 
-#[derive(Debug)]
+impl<T: AuthTypes> Clone for Session<T>
+where
+    T::AppData: Clone,
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            access_token: self.access_token.clone(),
+            token_type: self.token_type.clone(),
+            expires_in: self.expires_in,
+            expires_at: self.expires_at,
+            refresh_token: self.refresh_token.clone(),
+            user: self.user.clone(),
+        }
+    }
+}
+
+impl<T: AuthTypes> PartialEq for Session<T>
+where
+    T::AppData: PartialEq,
+    T::UserData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.access_token == other.access_token
+            && self.token_type == other.token_type
+            && self.expires_in == other.expires_in
+            && self.expires_at == other.expires_at
+            && self.refresh_token == other.refresh_token
+            && self.user == other.user
+    }
+}
+
+impl<T: AuthTypes> Eq for Session<T>
+where
+    T::AppData: Eq,
+    T::UserData: Eq,
+{
+}
+
+/// Sessions to terminate on logout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogoutScope {
+    /// All sessions of the user, signing out of all devices.
+    #[default]
+    Global,
+    /// Only the current session.
+    Local,
+    /// All sessions except the current one.
+    Others,
+}
+
+impl LogoutScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogoutScope::Global => "global",
+            LogoutScope::Local => "local",
+            LogoutScope::Others => "others",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EmailOrPhone {
     Email(String),
     Phone(String),
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
-pub struct User {
-    pub id: String,
-    pub email: String,
+/// A user as returned by the GoTrue REST API.
+///
+/// `app_metadata` and `user_metadata` are typed through the [`AuthTypes`] associated types, the
+/// same way as the JWT [`Claims`](crate::middleware::Claims).
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct User<T: AuthTypes = DefaultAuthTypes> {
+    pub id: Uuid,
     pub aud: String,
     pub role: String,
-    pub email_confirmed_at: Option<String>,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub email_confirmed_at: Option<OffsetDateTime>,
+    #[serde(default)]
     pub phone: String,
-    pub last_sign_in_at: Option<String>,
-    pub created_at: String,
-    pub updated_at: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub phone_confirmed_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub confirmed_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub confirmation_sent_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub invited_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub recovery_sent_at: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_email: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub email_change_sent_at: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_phone: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub phone_change_sent_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    pub app_metadata: AppMetadata<T::AppData>,
+    pub user_metadata: T::UserData,
+    #[serde(default)]
+    pub identities: Vec<Identity>,
+    #[serde(default)]
+    pub factors: Vec<Factor>,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub banned_until: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub deleted_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
 }
 
-#[derive(Debug)]
-pub struct UserAttributes {
-    pub email: String,
-    pub password: String,
-    pub data: serde_json::Value,
+impl<T: AuthTypes> Debug for User<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("User")
+            .field("id", &self.id)
+            .field("aud", &self.aud)
+            .field("role", &self.role)
+            .field("email", &self.email)
+            .field("email_confirmed_at", &self.email_confirmed_at)
+            .field("phone", &self.phone)
+            .field("phone_confirmed_at", &self.phone_confirmed_at)
+            .field("confirmed_at", &self.confirmed_at)
+            .field("confirmation_sent_at", &self.confirmation_sent_at)
+            .field("invited_at", &self.invited_at)
+            .field("recovery_sent_at", &self.recovery_sent_at)
+            .field("new_email", &self.new_email)
+            .field("email_change_sent_at", &self.email_change_sent_at)
+            .field("new_phone", &self.new_phone)
+            .field("phone_change_sent_at", &self.phone_change_sent_at)
+            .field("last_sign_in_at", &self.last_sign_in_at)
+            .field("app_metadata", &self.app_metadata)
+            .field("user_metadata", &self.user_metadata)
+            .field("identities", &self.identities)
+            .field("factors", &self.factors)
+            .field("is_anonymous", &self.is_anonymous)
+            .field("banned_until", &self.banned_until)
+            .field("deleted_at", &self.deleted_at)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for User<T>
+where
+    T::AppData: Clone,
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            aud: self.aud.clone(),
+            role: self.role.clone(),
+            email: self.email.clone(),
+            email_confirmed_at: self.email_confirmed_at,
+            phone: self.phone.clone(),
+            phone_confirmed_at: self.phone_confirmed_at,
+            confirmed_at: self.confirmed_at,
+            confirmation_sent_at: self.confirmation_sent_at,
+            invited_at: self.invited_at,
+            recovery_sent_at: self.recovery_sent_at,
+            new_email: self.new_email.clone(),
+            email_change_sent_at: self.email_change_sent_at,
+            new_phone: self.new_phone.clone(),
+            phone_change_sent_at: self.phone_change_sent_at,
+            last_sign_in_at: self.last_sign_in_at,
+            app_metadata: self.app_metadata.clone(),
+            user_metadata: self.user_metadata.clone(),
+            identities: self.identities.clone(),
+            factors: self.factors.clone(),
+            is_anonymous: self.is_anonymous,
+            banned_until: self.banned_until,
+            deleted_at: self.deleted_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl<T: AuthTypes> PartialEq for User<T>
+where
+    T::AppData: PartialEq,
+    T::UserData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.aud == other.aud
+            && self.role == other.role
+            && self.email == other.email
+            && self.email_confirmed_at == other.email_confirmed_at
+            && self.phone == other.phone
+            && self.phone_confirmed_at == other.phone_confirmed_at
+            && self.confirmed_at == other.confirmed_at
+            && self.confirmation_sent_at == other.confirmation_sent_at
+            && self.invited_at == other.invited_at
+            && self.recovery_sent_at == other.recovery_sent_at
+            && self.new_email == other.new_email
+            && self.email_change_sent_at == other.email_change_sent_at
+            && self.new_phone == other.new_phone
+            && self.phone_change_sent_at == other.phone_change_sent_at
+            && self.last_sign_in_at == other.last_sign_in_at
+            && self.app_metadata == other.app_metadata
+            && self.user_metadata == other.user_metadata
+            && self.identities == other.identities
+            && self.factors == other.factors
+            && self.is_anonymous == other.is_anonymous
+            && self.banned_until == other.banned_until
+            && self.deleted_at == other.deleted_at
+            && self.created_at == other.created_at
+            && self.updated_at == other.updated_at
+    }
+}
+
+impl<T: AuthTypes> Eq for User<T>
+where
+    T::AppData: Eq,
+    T::UserData: Eq,
+{
+}
+
+impl<T: AuthTypes> User<T> {
+    /// Returns whether the user is currently banned.
+    pub fn is_banned(&self) -> bool {
+        self.banned_until
+            .is_some_and(|banned_until| banned_until > OffsetDateTime::now_utc())
+    }
+}
+
+/// An identity of a user at an identity provider, e.g. `email` or `github`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Identity {
+    pub identity_id: Uuid,
+    /// The user's id at the provider.
+    pub id: String,
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub identity_data: serde_json::Value,
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A multi-factor authentication factor of a user.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Factor {
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub friendly_name: Option<String>,
+    pub factor_type: FactorType,
+    pub status: FactorStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_challenged_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FactorType {
+    Totp,
+    Phone,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FactorStatus {
+    Verified,
+    Unverified,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Attributes of the signed in user to update. Unset fields are left untouched.
+///
+/// Setting an email and password on an anonymous user converts it into a permanent user,
+/// keeping its id.
+#[derive(Serialize, Builder)]
+#[serde(bound = "")]
+pub struct UserAttributes<T: AuthTypes = DefaultAuthTypes> {
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T::UserData>,
+    /// Nonce sent by [`SessionAuth::reauthenticate`](crate::SessionAuth::reauthenticate),
+    /// required for password updates when secure password change is enabled.
+    #[builder(into)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl<T: AuthTypes> Default for UserAttributes<T> {
+    fn default() -> Self {
+        Self {
+            email: None,
+            phone: None,
+            password: None,
+            data: None,
+            nonce: None,
+        }
+    }
+}
+
+impl<T: AuthTypes> Debug for UserAttributes<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserAttributes")
+            .field("email", &self.email)
+            .field("phone", &self.phone)
+            .field("password", &self.password.as_ref().map(|_| "[redacted]"))
+            .field("data", &self.data)
+            .field("nonce", &self.nonce.as_ref().map(|_| "[redacted]"))
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for UserAttributes<T>
+where
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+            password: self.password.clone(),
+            data: self.data.clone(),
+            nonce: self.nonce.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub struct UserList<T: AuthTypes = DefaultAuthTypes> {
+    pub users: Vec<User<T>>,
+    /// Total number of users, taken from the `X-Total-Count` response header.
+    #[serde(skip)]
+    pub total: Option<u64>,
+    /// Next page to fetch, taken from the `Link` response header.
+    #[serde(skip)]
+    pub next_page: Option<u32>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct UserList {
-    pub users: Vec<User>,
+impl<T: AuthTypes> Debug for UserList<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserList")
+            .field("users", &self.users)
+            .field("total", &self.total)
+            .field("next_page", &self.next_page)
+            .finish()
+    }
+}
+
+impl<T: AuthTypes> Clone for UserList<T>
+where
+    T::AppData: Clone,
+    T::UserData: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            users: self.users.clone(),
+            total: self.total,
+            next_page: self.next_page,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -121,10 +498,26 @@ pub struct UserUpdate {
     pub updated_at: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OAuthRequest {
     pub provider: String,
-    pub redirect_to: String,
+    /// URL GoTrue redirects to after authorization. Falls back to the configured site URL.
+    pub redirect_to: Option<String>,
+}
+
+/// Selects the SSO identity provider to sign in with.
+#[derive(Debug, Clone)]
+pub enum SsoTarget {
+    /// Email domain registered with an SSO provider, e.g. `example.com`.
+    Domain(String),
+    ProviderId(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub struct SsoRequest {
+    pub target: SsoTarget,
+    /// URL GoTrue redirects to after authorization. Falls back to the configured site URL.
+    pub redirect_to: Option<String>,
 }
 
 #[derive(Debug)]
@@ -132,3 +525,72 @@ pub struct OAuthResponse {
     pub supabase_url: String,
     pub csrf_token: String,
 }
+
+impl OAuthResponse {
+    pub(crate) fn new(url: Url, pkce_verifier: &PkceCodeVerifier) -> Self {
+        Self {
+            supabase_url: url.to_string(),
+            csrf_token: BASE64_STANDARD.encode(pkce_verifier.secret()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = r#"{
+        "id": "34abc1f7-e346-4b30-bc26-1b53f707bf54",
+        "aud": "authenticated",
+        "role": "authenticated",
+        "email": "testuser@test.com",
+        "email_confirmed_at": "2024-08-18T09:27:49.412964Z",
+        "phone": "",
+        "confirmed_at": "2024-08-18T09:27:49.412964Z",
+        "last_sign_in_at": "2024-08-18T09:27:49.417021Z",
+        "app_metadata": {"provider": "email", "providers": ["email"], "groups": ["fk"]},
+        "user_metadata": {"name": "Test"},
+        "identities": [{
+            "identity_id": "6e1d8c8b-5d2c-4d5c-9f3f-2f5b3c7a1d0e",
+            "id": "34abc1f7-e346-4b30-bc26-1b53f707bf54",
+            "user_id": "34abc1f7-e346-4b30-bc26-1b53f707bf54",
+            "identity_data": {"email": "testuser@test.com", "sub": "34abc1f7-e346-4b30-bc26-1b53f707bf54"},
+            "provider": "email",
+            "last_sign_in_at": "2024-08-18T09:27:49.409813Z",
+            "created_at": "2024-08-18T09:27:49.409856Z",
+            "updated_at": "2024-08-18T09:27:49.409856Z",
+            "email": "testuser@test.com"
+        }],
+        "factors": [{
+            "id": "0c6d4e2a-8f8e-4f7e-9c59-7a1f5c3c6b2d",
+            "friendly_name": "phone",
+            "factor_type": "totp",
+            "status": "verified",
+            "created_at": "2024-08-18T09:30:00Z",
+            "updated_at": "2024-08-18T09:30:00Z"
+        }],
+        "created_at": "2024-08-18T09:27:49.406108Z",
+        "updated_at": "2024-08-18T09:27:49.419004Z",
+        "is_anonymous": false
+    }"#;
+
+    #[test]
+    fn deserialize_user() {
+        let user: User = serde_json::from_str(USER).unwrap();
+
+        assert_eq!(
+            user.id,
+            Uuid::parse_str("34abc1f7-e346-4b30-bc26-1b53f707bf54").unwrap()
+        );
+        assert_eq!(user.app_metadata.provider, "email");
+        assert_eq!(user.app_metadata.additional["groups"][0], "fk");
+        assert_eq!(user.user_metadata["name"], "Test");
+        assert_eq!(user.identities[0].provider, "email");
+        assert_eq!(user.factors[0].factor_type, FactorType::Totp);
+        assert_eq!(user.factors[0].status, FactorStatus::Verified);
+        assert!(user.email_confirmed_at.is_some());
+        assert!(user.phone_confirmed_at.is_none());
+        assert!(!user.is_anonymous);
+        assert!(!user.is_banned());
+    }
+}
@@ -16,29 +16,46 @@ where
 {
     Router::new()
         .route("/login", post(post::login))
+        .route("/signup", post(post::signup))
+        .route("/signup/resend", post(post::resend_signup))
+        .route("/login/anonymous", post(post::login_anonymously))
+        .route("/token/id_token", post(post::token_id_token))
         .route("/logout", post(post::logout))
+        .route("/refresh", post(post::refresh))
         .route("/login/github", get(get::login_github))
         .route("/login/confirm", get(get::login_confirm))
+        .route("/login/sso", post(post::login_sso))
+        .route("/link/:provider", get(get::link_identity))
+        .route("/unlink", post(post::unlink_identity))
+        .route("/password", post(post::update_password))
 }
 
 mod post {
-    use crate::auth::SessionAuth;
-    use crate::handlers::set_cookies_from_session;
-    use crate::middleware::{AccessToken, MaybeUser};
+    use crate::api::{IdTokenCredentials, ResendRequest, ResendType, SignUpOptions};
+    use crate::auth::{ClientError, SessionAuth};
+    use crate::handlers::{append_query, set_cookies_from_session, set_csrf_verifier_cookie};
+    use crate::middleware::{AccessToken, AuthEvents, AuthMethod, MaybeUser, RequestMeta};
     use crate::AuthState;
-    use crate::{Auth, AuthTypes, EmailOrPhone};
-    use axum::extract::State;
-    use axum::http::StatusCode;
-    use axum::response::{IntoResponse, Redirect};
-    use axum::Form;
+    use crate::{
+        Auth, AuthTypes, EmailOrPhone, LogoutScope, SsoRequest, SsoTarget, UserAttributes,
+    };
+    use axum::extract::{FromRequest, Query, Request, State};
+    use axum::http::{header, StatusCode};
+    use axum::response::{IntoResponse, Redirect, Response};
+    use axum::{async_trait, Form, Json};
     use axum_extra::extract::CookieJar;
-    use serde::Deserialize;
+    use serde::de::{self, DeserializeOwned};
+    use serde::{Deserialize, Deserializer};
+    use serde_json::Value;
     use tracing::warn;
+    use uuid::Uuid;
 
     #[derive(Debug, Clone, Deserialize)]
     pub struct Credentials {
         pub email: String,
         pub password: String,
+        /// hCaptcha or Turnstile token, required when captcha protection is enabled.
+        pub captcha_token: Option<String>,
         pub next: Option<String>,
     }
 
@@ -46,6 +63,7 @@ mod post {
         jar: CookieJar,
         State(auth): State<AuthState<T>>,
         MaybeUser(claims): MaybeUser<T>,
+        meta: RequestMeta,
         Form(creds): Form<Credentials>,
     ) -> impl IntoResponse
     where
@@ -57,53 +75,513 @@ mod post {
 
         let session = match auth
             .auth()
-            .sign_in(EmailOrPhone::Email(creds.email), &creds.password)
+            .sign_in(
+                EmailOrPhone::Email(creds.email.clone()),
+                &creds.password,
+                creds.captcha_token,
+            )
             .await
         {
             Ok(session) => session,
             // TODO: handle diffferent errors.
-            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Err(err) => {
+                auth.events()
+                    .sign_in_failed(&AuthMethod::Password, Some(&creds.email), &err, &meta)
+                    .await;
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
         };
 
+        auth.events()
+            .signed_in(&session.user, &AuthMethod::Password, &meta)
+            .await;
+
         let jar = set_cookies_from_session(auth.cookies(), jar, session);
         let next = creds.next.unwrap_or_else(|| "/profile".to_string());
 
         (jar, Redirect::to(&next)).into_response()
     }
 
+    /// Sign-up request, either a form or, with `Content-Type: application/json`, a JSON body.
+    #[derive(Deserialize)]
+    #[serde(bound = "")]
+    pub struct SignUp<T: AuthTypes> {
+        pub email: String,
+        pub password: String,
+        /// URL the confirmation link redirects to.
+        pub email_redirect_to: Option<String>,
+        pub captcha_token: Option<String>,
+        pub next: Option<String>,
+        /// Stored as `user_metadata`. A nested object in a JSON body, a JSON encoded object in
+        /// a form.
+        #[serde(default, deserialize_with = "json_object")]
+        pub data: Option<T::UserData>,
+    }
+
+    #[async_trait]
+    impl<S, T> FromRequest<S> for SignUp<T>
+    where
+        S: Send + Sync,
+        T: AuthTypes,
+    {
+        type Rejection = Response;
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let is_json = req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("application/json"));
+
+            if is_json {
+                let Json(sign_up) = Json::from_request(req, state)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                Ok(sign_up)
+            } else {
+                let Form(sign_up) = Form::from_request(req, state)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                Ok(sign_up)
+            }
+        }
+    }
+
+    /// Deserializes a nested object, or a string holding a JSON encoded object as sent by a form.
+    fn json_object<'de, D, U>(deserializer: D) -> Result<Option<U>, D::Error>
+    where
+        D: Deserializer<'de>,
+        U: DeserializeOwned,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::String(json) => serde_json::from_str(&json),
+            value => serde_json::from_value(value),
+        }
+        .map(Some)
+        .map_err(de::Error::custom)
+    }
+
+    /// Signs up with email and password. With autoconfirm the session cookies are set and the
+    /// user is redirected to `next`, otherwise to `/login?confirmation=sent`.
+    pub async fn signup<T>(
+        jar: CookieJar,
+        State(state): State<AuthState<T>>,
+        MaybeUser(claims): MaybeUser<T>,
+        meta: RequestMeta,
+        form: SignUp<T>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        if claims.is_some() {
+            return Redirect::to("/").into_response();
+        }
+
+        let options = SignUpOptions::<T>::builder()
+            .maybe_data(form.data)
+            .maybe_email_redirect_to(form.email_redirect_to)
+            .maybe_captcha_token(form.captcha_token)
+            .build();
+
+        let response = match state
+            .auth()
+            .sign_up(
+                EmailOrPhone::Email(form.email.clone()),
+                &form.password,
+                options,
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                state
+                    .events()
+                    .sign_in_failed(&AuthMethod::Password, Some(&form.email), &err, &meta)
+                    .await;
+
+                return match err {
+                    ClientError::AlreadySignedUp => StatusCode::CONFLICT,
+                    ClientError::SignUpDisabled => StatusCode::FORBIDDEN,
+                    ClientError::WeakPassword | ClientError::InvalidSignUp(_) => {
+                        StatusCode::UNPROCESSABLE_ENTITY
+                    }
+                    err => {
+                        warn!(%err, "signing up failed");
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                }
+                .into_response();
+            }
+        };
+
+        match response.session() {
+            Some(session) => {
+                state
+                    .events()
+                    .signed_in(&session.user, &AuthMethod::Password, &meta)
+                    .await;
+
+                let jar = set_cookies_from_session(state.cookies(), jar, session);
+                let next = form.next.unwrap_or_else(|| "/profile".to_string());
+                (jar, Redirect::to(&next)).into_response()
+            }
+            None => Redirect::to(&append_query("/login", "confirmation", "sent")).into_response(),
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct ResendSignup {
+        pub email: String,
+        /// URL the new confirmation link redirects to.
+        pub email_redirect_to: Option<String>,
+        pub captcha_token: Option<String>,
+        pub next: Option<String>,
+    }
+
+    /// Resends the signup confirmation mail. Responds with `429 Too Many Requests` when the
+    /// email rate limit is exceeded.
+    pub async fn resend_signup<T>(
+        State(state): State<AuthState<T>>,
+        Form(form): Form<ResendSignup>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let request = ResendRequest::builder()
+            .kind(ResendType::Signup)
+            .target(EmailOrPhone::Email(form.email))
+            .maybe_email_redirect_to(form.email_redirect_to)
+            .maybe_captcha_token(form.captcha_token)
+            .build();
+
+        match state.auth().resend(request).await {
+            Ok(()) => {}
+            Err(ClientError::EmailRateLimited | ClientError::RateLimited { .. }) => {
+                return StatusCode::TOO_MANY_REQUESTS.into_response()
+            }
+            Err(err) => {
+                warn!(%err, "resending signup confirmation failed");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let next = form.next.unwrap_or_else(|| "/login".to_string());
+        Redirect::to(&next).into_response()
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct AnonymousSignIn {
+        pub captcha_token: Option<String>,
+        pub next: Option<String>,
+    }
+
+    pub async fn login_anonymously<T>(
+        jar: CookieJar,
+        State(auth): State<AuthState<T>>,
+        MaybeUser(claims): MaybeUser<T>,
+        meta: RequestMeta,
+        Form(form): Form<AnonymousSignIn>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        if claims.is_some() {
+            return Redirect::to("/").into_response();
+        }
+
+        let session = match auth
+            .auth()
+            .sign_in_anonymously(None, form.captcha_token)
+            .await
+        {
+            Ok(session) => session,
+            Err(err) => {
+                auth.events()
+                    .sign_in_failed(&AuthMethod::Anonymous, None, &err, &meta)
+                    .await;
+                return match err {
+                    ClientError::AnonymousSignInDisabled => StatusCode::FORBIDDEN,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+                .into_response();
+            }
+        };
+
+        auth.events()
+            .signed_in(&session.user, &AuthMethod::Anonymous, &meta)
+            .await;
+
+        let jar = set_cookies_from_session(auth.cookies(), jar, session);
+        let next = form.next.unwrap_or_else(|| "/".to_string());
+
+        (jar, Redirect::to(&next)).into_response()
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct SsoLogin {
+        /// Email domain of the user. A full email address is accepted as well.
+        pub domain: String,
+        pub redirect_to: Option<String>,
+    }
+
+    pub async fn login_sso<T>(
+        jar: CookieJar,
+        State(state): State<AuthState<T>>,
+        Form(form): Form<SsoLogin>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let domain = match form.domain.rsplit_once('@') {
+            Some((_, domain)) => domain,
+            None => form.domain.as_str(),
+        };
+
+        let response = match state
+            .auth()
+            .sign_in_with_sso(SsoRequest {
+                target: SsoTarget::Domain(domain.trim().to_lowercase()),
+                redirect_to: form.redirect_to,
+            })
+            .await
+        {
+            Ok(response) => response,
+            Err(ClientError::SsoProviderNotFound) => return StatusCode::NOT_FOUND.into_response(),
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+
+        let jar = set_csrf_verifier_cookie(state.cookies(), jar, response.csrf_token);
+
+        (jar, Redirect::to(&response.supabase_url)).into_response()
+    }
+
+    /// Exchanges a natively obtained OIDC ID token for a session, returned as JSON for native
+    /// clients instead of setting cookies.
+    pub async fn token_id_token<T>(
+        State(state): State<AuthState<T>>,
+        meta: RequestMeta,
+        Json(credentials): Json<IdTokenCredentials>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let method = AuthMethod::IdToken(credentials.provider.clone());
+
+        match state.auth().sign_in_with_id_token(credentials).await {
+            Ok(session) => {
+                state
+                    .events()
+                    .signed_in(&session.user, &method, &meta)
+                    .await;
+                Json(session).into_response()
+            }
+            Err(err) => {
+                state
+                    .events()
+                    .sign_in_failed(&method, None, &err, &meta)
+                    .await;
+                match err {
+                    ClientError::WrongToken | ClientError::WrongCredentials => {
+                        StatusCode::UNAUTHORIZED.into_response()
+                    }
+                    _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    pub struct LogoutParams {
+        #[serde(default)]
+        pub scope: LogoutScope,
+    }
+
+    /// Logs out with the scope given as `?scope=global|local|others`, defaulting to `global`.
+    /// With scope `others` the current session stays signed in.
     pub async fn logout<T>(
         jar: CookieJar,
         State(state): State<AuthState<T>>,
         token: AccessToken<T>,
+        meta: RequestMeta,
+        Query(LogoutParams { scope }): Query<LogoutParams>,
     ) -> impl IntoResponse
     where
         T: AuthTypes,
     {
-        let jar = jar.remove(state.cookies().refresh_cookie_name().to_string());
-        let jar = jar.remove(state.cookies().auth_cookie_name().to_string());
+        let jar = match scope {
+            LogoutScope::Others => jar,
+            LogoutScope::Global | LogoutScope::Local => jar
+                .remove(state.cookies().refresh_cookie_name().to_string())
+                .remove(state.cookies().auth_cookie_name().to_string()),
+        };
 
+        let claims = state.decode(token.as_ref()).ok();
         let client = state.auth().with_token(token.into());
-        if let Err(err) = client.logout().await {
+        if let Err(err) = client.logout(scope).await {
             warn!(%err, "logout failed");
             return (jar, StatusCode::INTERNAL_SERVER_ERROR).into_response();
         };
 
-        (jar, Redirect::to("/login")).into_response()
+        if let Some(claims) = claims {
+            state.events().signed_out(&claims, scope, &meta).await;
+        }
+
+        match scope {
+            LogoutScope::Others => (jar, Redirect::to("/profile")).into_response(),
+            LogoutScope::Global | LogoutScope::Local => {
+                (jar, Redirect::to("/login")).into_response()
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Default, Deserialize)]
+    pub struct RefreshParams {
+        pub next: Option<String>,
+    }
+
+    /// Refreshes the session from the refresh cookie, sets the new cookies and redirects to
+    /// `?next=`, defaulting to `/`.
+    pub async fn refresh<T>(
+        jar: CookieJar,
+        State(state): State<AuthState<T>>,
+        meta: RequestMeta,
+        Query(RefreshParams { next }): Query<RefreshParams>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let refresh_token = match jar.get(state.cookies().refresh_cookie_name()) {
+            Some(refresh_token) => refresh_token.value_trimmed().to_string(),
+            None => return StatusCode::UNAUTHORIZED.into_response(),
+        };
+        let access_token = jar
+            .get(state.cookies().auth_cookie_name())
+            .map(|access_token| access_token.value_trimmed().to_string())
+            .unwrap_or_default();
+
+        let mut client = state
+            .auth()
+            .with_refresh_token(access_token.into(), refresh_token.into());
+        let session = match client.refresh().await {
+            Ok(session) => session,
+            Err(err) => {
+                warn!(%err, "refreshing session failed");
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+        };
+
+        state.events().refreshed(&session.user, &meta).await;
+
+        let jar = set_cookies_from_session(state.cookies(), jar, session);
+        let next = next.unwrap_or_else(|| "/".to_string());
+
+        (jar, Redirect::to(&next)).into_response()
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct UnlinkIdentity {
+        pub identity_id: Uuid,
+        pub next: Option<String>,
+    }
+
+    pub async fn unlink_identity<T>(
+        State(state): State<AuthState<T>>,
+        token: AccessToken<T>,
+        Form(form): Form<UnlinkIdentity>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let client = state.auth().with_token(token.into());
+        match client.unlink_identity(form.identity_id).await {
+            Ok(()) => {}
+            Err(ClientError::IdentityNotFound) => return StatusCode::NOT_FOUND.into_response(),
+            Err(ClientError::SingleIdentityNotDeletable) => {
+                return StatusCode::UNPROCESSABLE_ENTITY.into_response()
+            }
+            Err(ClientError::ManualLinkingDisabled) => {
+                return StatusCode::FORBIDDEN.into_response()
+            }
+            Err(err) => {
+                warn!(%err, "unlinking identity failed");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let next = form.next.unwrap_or_else(|| "/profile".to_string());
+        Redirect::to(&next).into_response()
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct PasswordUpdate {
+        pub password: String,
+        /// Reauthentication nonce, once the user received it.
+        pub nonce: Option<String>,
+        pub next: Option<String>,
+        /// Page asking for the nonce, which submits this form again with `nonce` set.
+        pub nonce_page: Option<String>,
+    }
+
+    /// Updates the password in two steps when secure password change is enabled: without a
+    /// nonce a reauthentication nonce is sent and the user is redirected to `nonce_page` with
+    /// `?reauthentication=sent`, an invalid nonce redirects there with `?reauthentication=invalid`.
+    pub async fn update_password<T>(
+        State(state): State<AuthState<T>>,
+        token: AccessToken<T>,
+        Form(form): Form<PasswordUpdate>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        let nonce_page = form
+            .nonce_page
+            .unwrap_or_else(|| "/profile/password".to_string());
+
+        let client = state.auth().with_token(token.into());
+        let attributes = UserAttributes::builder()
+            .password(form.password)
+            .maybe_nonce(form.nonce)
+            .build();
+
+        match client.update_user(attributes).await {
+            Ok(_) => {}
+            Err(ClientError::ReauthenticationNeeded) => {
+                if let Err(err) = client.reauthenticate().await {
+                    warn!(%err, "sending reauthentication nonce failed");
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+                let to = append_query(&nonce_page, "reauthentication", "sent");
+                return Redirect::to(&to).into_response();
+            }
+            Err(ClientError::ReauthenticationNotValid) => {
+                let to = append_query(&nonce_page, "reauthentication", "invalid");
+                return Redirect::to(&to).into_response();
+            }
+            Err(ClientError::WeakPassword) => {
+                return StatusCode::UNPROCESSABLE_ENTITY.into_response()
+            }
+            Err(err) => {
+                warn!(%err, "updating password failed");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let next = form.next.unwrap_or_else(|| "/profile".to_string());
+        Redirect::to(&next).into_response()
     }
 }
 
 mod get {
-    use crate::handlers::set_cookies_from_session;
-    use crate::middleware::AuthState;
+    use crate::auth::{ClientError, SessionAuth};
+    use crate::handlers::{set_cookies_from_session, set_csrf_verifier_cookie};
+    use crate::middleware::{AccessToken, AuthEvents, AuthMethod, AuthState, RequestMeta};
     use crate::{Auth, AuthTypes, OAuthRequest};
-    use axum::extract::{Query, State};
+    use axum::extract::{Path, Query, State};
     use axum::http::StatusCode;
     use axum::response::{IntoResponse, Redirect};
-    use axum_extra::extract::cookie::Cookie;
     use axum_extra::extract::CookieJar;
     use serde::Deserialize;
-    use std::ops::Add;
-    use time::{Duration, OffsetDateTime};
+    use tracing::warn;
 
     pub async fn login_github<T>(
         jar: CookieJar,
@@ -112,26 +590,81 @@ mod get {
     where
         T: AuthTypes,
     {
+        if !provider_enabled(&state, "github").await {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+
         let response = match state.auth().create_oauth_url(OAuthRequest {
             provider: "github".to_string(),
-            redirect_to: "https%3A%2F%2Fhp-rs-htmx.fly.dev%2Flogin%2Fconfirm".to_string(),
+            redirect_to: Some("https://hp-rs-htmx.fly.dev/login/confirm".to_string()),
         }) {
             Ok(response) => response,
             Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         };
 
-        let csrf_token = Cookie::build((
-            state.cookies().csrf_verifier_cookie_name().to_string(),
-            response.csrf_token,
-        ))
-        .expires(OffsetDateTime::now_utc().add(Duration::minutes(2)))
-        .secure(true);
+        let jar = set_csrf_verifier_cookie(state.cookies(), jar, response.csrf_token);
+
+        (jar, Redirect::to(&response.supabase_url)).into_response()
+    }
+
+    #[derive(Deserialize)]
+    pub struct LinkParams {
+        redirect_to: Option<String>,
+    }
+
+    pub async fn link_identity<T>(
+        jar: CookieJar,
+        State(state): State<AuthState<T>>,
+        token: AccessToken<T>,
+        Path(provider): Path<String>,
+        Query(LinkParams { redirect_to }): Query<LinkParams>,
+    ) -> impl IntoResponse
+    where
+        T: AuthTypes,
+    {
+        if !provider_enabled(&state, &provider).await {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+
+        let client = state.auth().with_token(token.into());
+        let response = match client
+            .link_identity(OAuthRequest {
+                provider,
+                redirect_to,
+            })
+            .await
+        {
+            Ok(response) => response,
+            Err(ClientError::ManualLinkingDisabled) => {
+                return StatusCode::FORBIDDEN.into_response()
+            }
+            Err(ClientError::IdentityAlreadyExists) => return StatusCode::CONFLICT.into_response(),
+            Err(err) => {
+                warn!(%err, "linking identity failed");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
 
-        let jar = jar.add(csrf_token.build());
+        let jar = set_csrf_verifier_cookie(state.cookies(), jar, response.csrf_token);
 
         (jar, Redirect::to(&response.supabase_url)).into_response()
     }
 
+    /// Rejects disabled providers before redirecting to GoTrue. If the settings can't be
+    /// fetched, GoTrue is left to reject the request.
+    async fn provider_enabled<T>(state: &AuthState<T>, provider: &str) -> bool
+    where
+        T: AuthTypes,
+    {
+        match state.settings().await {
+            Ok(settings) => settings.is_provider_enabled(provider),
+            Err(err) => {
+                warn!(%err, "fetching settings failed");
+                true
+            }
+        }
+    }
+
     #[derive(Deserialize)]
     pub struct ConfirmParams {
         code: String,
@@ -140,6 +673,7 @@ mod get {
     pub async fn login_confirm<T>(
         jar: CookieJar,
         State(state): State<AuthState<T>>,
+        meta: RequestMeta,
         Query(ConfirmParams { code }): Query<ConfirmParams>,
     ) -> impl IntoResponse
     where
@@ -156,9 +690,21 @@ mod get {
             .await
         {
             Ok(session) => session,
-            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Err(err) => {
+                state
+                    .events()
+                    .sign_in_failed(&AuthMethod::OAuth(None), None, &err, &meta)
+                    .await;
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
         };
 
+        let method = AuthMethod::from_pkce_provider(&session.user.app_metadata.provider);
+        state
+            .events()
+            .signed_in(&session.user, &method, &meta)
+            .await;
+
         let jar = set_cookies_from_session(state.cookies(), jar, session);
         let jar = jar.remove(state.cookies().csrf_verifier_cookie_name().to_string());
 
@@ -166,10 +712,32 @@ mod get {
     }
 }
 
-fn set_cookies_from_session(
+fn append_query(path: &str, key: &str, value: &str) -> String {
+    let separator = if path.contains('?') { '&' } else { '?' };
+    format!("{path}{separator}{key}={value}")
+}
+
+fn set_csrf_verifier_cookie(
+    cookie_config: &CookieConfig,
+    jar: CookieJar,
+    csrf_token: String,
+) -> CookieJar {
+    let csrf_cookie = Cookie::build((
+        cookie_config.csrf_verifier_cookie_name().to_string(),
+        csrf_token,
+    ))
+    .path("/")
+    .expires(OffsetDateTime::now_utc().add(Duration::minutes(2)))
+    .secure(true)
+    .build();
+
+    jar.add(csrf_cookie)
+}
+
+fn set_cookies_from_session<T: AuthTypes>(
     cookie_config: &CookieConfig,
     jar: CookieJar,
-    session: Session,
+    session: Session<T>,
 ) -> CookieJar {
     let expires = OffsetDateTime::now_utc().add(Duration::seconds(session.expires_in as i64));
     let auth_cookie = Cookie::build((
@@ -0,0 +1,164 @@
+//! Receives [Supabase Auth Hooks] sent as HTTP requests, signed with the Standard Webhooks
+//! scheme.
+//!
+//! [`HooksRouter`] serves the hooks that are registered on it, each of them on its own path, and
+//! verifies every request with a [`WebhookVerifier`]. Handlers for other routes can verify hooks
+//! with the [`Webhook`] extractor instead.
+//!
+//! [Supabase Auth Hooks]: https://supabase.com/docs/guides/auth/auth-hooks
+
+mod types;
+mod webhook;
+
+use crate::{AuthTypes, DefaultAuthTypes};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::future::Future;
+use std::marker::PhantomData;
+use thiserror::Error;
+pub use types::*;
+pub use webhook::*;
+
+pub const CUSTOM_ACCESS_TOKEN_PATH: &str = "/custom-access-token";
+pub const SEND_EMAIL_PATH: &str = "/send-email";
+pub const SEND_SMS_PATH: &str = "/send-sms";
+pub const MFA_VERIFICATION_ATTEMPT_PATH: &str = "/mfa-verification-attempt";
+pub const PASSWORD_VERIFICATION_ATTEMPT_PATH: &str = "/password-verification-attempt";
+
+/// Error returned by a hook. GoTrue fails the request that invoked the hook and shows
+/// `message` to the user.
+#[derive(Debug, Clone, Serialize, Deserialize, Error)]
+#[error("Hook failed with status {http_code}: {message}")]
+pub struct HookError {
+    pub http_code: u16,
+    pub message: String,
+}
+
+impl HookError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            http_code: status.as_u16(),
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for HookError {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.http_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(json!({ "error": self }))).into_response()
+    }
+}
+
+/// Builds a router serving the registered hooks, e.g. nested under `/hooks`:
+///
+/// ```no_run
+/// # use axum::Router;
+/// # use axum_supabase_auth::hooks::{HooksRouter, SendEmailInput, WebhookVerifier};
+/// # use axum_supabase_auth::DefaultAuthTypes;
+/// let verifier = WebhookVerifier::new("v1,whsec_c2VjcmV0").unwrap();
+/// let hooks = HooksRouter::<DefaultAuthTypes>::new(verifier)
+///     .send_email(|input: SendEmailInput| async move {
+///         println!("sending {:?} mail", input.email_data.email_action_type);
+///         Ok(())
+///     });
+///
+/// let app: Router = Router::new().nest("/hooks", hooks.router());
+/// ```
+///
+/// The hook URL configured in Supabase then is e.g. `https://example.com/hooks/send-email`.
+pub struct HooksRouter<T: AuthTypes = DefaultAuthTypes> {
+    router: Router<WebhookVerifier>,
+    verifier: WebhookVerifier,
+    phantom_data: PhantomData<T>,
+}
+
+impl<T: AuthTypes> HooksRouter<T> {
+    pub fn new(verifier: WebhookVerifier) -> Self {
+        Self {
+            router: Router::new(),
+            verifier,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Serves the custom access token hook on [`CUSTOM_ACCESS_TOKEN_PATH`].
+    pub fn custom_access_token<H, F>(self, handler: H) -> Self
+    where
+        H: Fn(CustomAccessTokenInput<T>) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<CustomAccessTokenOutput<T>, HookError>> + Send + 'static,
+    {
+        self.route(CUSTOM_ACCESS_TOKEN_PATH, handler, Json)
+    }
+
+    /// Serves the send email hook on [`SEND_EMAIL_PATH`].
+    pub fn send_email<H, F>(self, handler: H) -> Self
+    where
+        H: Fn(SendEmailInput<T>) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<(), HookError>> + Send + 'static,
+    {
+        self.route(SEND_EMAIL_PATH, handler, empty)
+    }
+
+    /// Serves the send SMS hook on [`SEND_SMS_PATH`].
+    pub fn send_sms<H, F>(self, handler: H) -> Self
+    where
+        H: Fn(SendSmsInput<T>) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<(), HookError>> + Send + 'static,
+    {
+        self.route(SEND_SMS_PATH, handler, empty)
+    }
+
+    /// Serves the MFA verification attempt hook on [`MFA_VERIFICATION_ATTEMPT_PATH`].
+    pub fn mfa_verification_attempt<H, F>(self, handler: H) -> Self
+    where
+        H: Fn(MfaVerificationAttemptInput) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<MfaVerificationAttemptOutput, HookError>> + Send + 'static,
+    {
+        self.route(MFA_VERIFICATION_ATTEMPT_PATH, handler, Json)
+    }
+
+    /// Serves the password verification attempt hook on
+    /// [`PASSWORD_VERIFICATION_ATTEMPT_PATH`].
+    pub fn password_verification_attempt<H, F>(self, handler: H) -> Self
+    where
+        H: Fn(PasswordVerificationAttemptInput) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<PasswordVerificationAttemptOutput, HookError>> + Send + 'static,
+    {
+        self.route(PASSWORD_VERIFICATION_ATTEMPT_PATH, handler, Json)
+    }
+
+    pub fn router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        self.router.with_state(self.verifier)
+    }
+
+    fn route<I, O, R, H, F>(mut self, path: &str, handler: H, respond: fn(O) -> R) -> Self
+    where
+        I: for<'de> Deserialize<'de> + Send + 'static,
+        O: Send + 'static,
+        R: IntoResponse + 'static,
+        H: Fn(I) -> F + Clone + Send + Sync + 'static,
+        F: Future<Output = Result<O, HookError>> + Send + 'static,
+    {
+        let handler = move |Webhook(input): Webhook<I>| async move {
+            match handler(input).await {
+                Ok(output) => respond(output).into_response(),
+                Err(err) => err.into_response(),
+            }
+        };
+        self.router = self.router.route(path, post(handler));
+        self
+    }
+}
+
+fn empty(_: ()) -> Json<serde_json::Value> {
+    Json(json!({}))
+}
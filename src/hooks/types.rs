@@ -0,0 +1,157 @@
+use crate::middleware::AuthClaims;
+use crate::{AuthTypes, DefaultAuthTypes, User};
+use serde::de::{self, Deserializer};
+use serde::ser::{self, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Payload of the custom access token hook, invoked before a token is issued.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CustomAccessTokenInput<T: AuthTypes = DefaultAuthTypes> {
+    pub user_id: Uuid,
+    pub claims: HookClaims<T>,
+    /// E.g. `password`, `otp`, `oauth` or `token_refresh`.
+    pub authentication_method: String,
+}
+
+/// Claims the access token is issued with. They must still contain the required claims.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CustomAccessTokenOutput<T: AuthTypes = DefaultAuthTypes> {
+    pub claims: HookClaims<T>,
+}
+
+/// Claims of the custom access token hook. `rest` keeps the claims not captured by the typed
+/// `claims`, e.g. `aud`, `iat`, `aal`, `amr`, `session_id` and `iss` with a typed
+/// [`AuthTypes::AdditionalData`], so they are sent back unchanged.
+pub struct HookClaims<T: AuthTypes = DefaultAuthTypes> {
+    pub claims: AuthClaims<T>,
+    pub rest: Map<String, Value>,
+}
+
+impl<T: AuthTypes> Serialize for HookClaims<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut claims = match serde_json::to_value(&self.claims).map_err(ser::Error::custom)? {
+            Value::Object(claims) => claims,
+            _ => return Err(ser::Error::custom("claims are not an object")),
+        };
+        for (name, value) in &self.rest {
+            claims.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+
+        claims.serialize(serializer)
+    }
+}
+
+impl<'de, T: AuthTypes> Deserialize<'de> for HookClaims<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let all = Map::deserialize(deserializer)?;
+        let claims: AuthClaims<T> =
+            serde_json::from_value(Value::Object(all.clone())).map_err(de::Error::custom)?;
+        let typed = match serde_json::to_value(&claims).map_err(de::Error::custom)? {
+            Value::Object(typed) => typed,
+            _ => Map::new(),
+        };
+        let rest = all
+            .into_iter()
+            .filter(|(name, _)| !typed.contains_key(name))
+            .collect();
+
+        Ok(Self { claims, rest })
+    }
+}
+
+/// Payload of the send email hook, replacing the emails GoTrue sends itself.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SendEmailInput<T: AuthTypes = DefaultAuthTypes> {
+    pub user: User<T>,
+    pub email_data: EmailData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailData {
+    pub token: String,
+    pub token_hash: String,
+    pub redirect_to: String,
+    pub email_action_type: EmailActionType,
+    pub site_url: String,
+    /// OTP of the new address on an email change with secure email change enabled.
+    #[serde(default)]
+    pub token_new: String,
+    #[serde(default)]
+    pub token_hash_new: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum EmailActionType {
+    Signup,
+    Invite,
+    Magiclink,
+    Recovery,
+    EmailChange,
+    Email,
+    Reauthentication,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Payload of the send SMS hook, replacing the configured SMS provider.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SendSmsInput<T: AuthTypes = DefaultAuthTypes> {
+    pub user: User<T>,
+    pub sms: SmsData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsData {
+    pub otp: String,
+}
+
+/// Payload of the MFA verification attempt hook, invoked after each MFA verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaVerificationAttemptInput {
+    pub factor_id: Uuid,
+    #[serde(default)]
+    pub factor_type: String,
+    pub user_id: Uuid,
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaVerificationAttemptOutput {
+    pub decision: HookDecision,
+    /// Shown to the user on rejection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Payload of the password verification attempt hook, invoked after each password sign-in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordVerificationAttemptInput {
+    pub user_id: Uuid,
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordVerificationAttemptOutput {
+    pub decision: HookDecision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Signs the user out of all sessions on rejection.
+    #[serde(default)]
+    pub should_logout_user: bool,
+}
+
+/// Whether a verification attempt may continue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookDecision {
+    Continue,
+    Reject,
+}
@@ -0,0 +1,199 @@
+use super::HookError;
+use axum::async_trait;
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+pub const WEBHOOK_ID: &str = "webhook-id";
+pub const WEBHOOK_TIMESTAMP: &str = "webhook-timestamp";
+pub const WEBHOOK_SIGNATURE: &str = "webhook-signature";
+
+/// Maximum difference between the webhook timestamp and now, guarding against replays.
+pub const DEFAULT_WEBHOOK_TOLERANCE: Duration = Duration::from_secs(5 * 60);
+
+/// Verifies requests signed with the [Standard Webhooks] scheme Supabase signs hooks with.
+///
+/// [Standard Webhooks]: https://www.standardwebhooks.com
+#[derive(Clone)]
+pub struct WebhookVerifier {
+    key: Arc<[u8]>,
+    tolerance: Duration,
+}
+
+impl WebhookVerifier {
+    /// Creates a verifier from the hook secret shown in the dashboard, `v1,whsec_<base64>`.
+    /// The `v1,` and `whsec_` prefixes are optional.
+    pub fn new(secret: &str) -> Result<Self, InvalidWebhookSecret> {
+        let secret = secret.strip_prefix("v1,").unwrap_or(secret);
+        let secret = secret.strip_prefix("whsec_").unwrap_or(secret);
+        let key = BASE64_STANDARD.decode(secret)?;
+
+        Ok(Self {
+            key: key.into(),
+            tolerance: DEFAULT_WEBHOOK_TOLERANCE,
+        })
+    }
+
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Verifies the signature and timestamp headers of a webhook with `body`.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), WebhookError> {
+        let header = |name: &'static str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or(WebhookError::MissingHeader(name))
+        };
+        let id = header(WEBHOOK_ID)?;
+        let timestamp = header(WEBHOOK_TIMESTAMP)?;
+        let signatures = header(WEBHOOK_SIGNATURE)?;
+
+        let sent_at = timestamp
+            .parse::<i64>()
+            .map_err(|_| WebhookError::InvalidTimestamp)?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if now.abs_diff(sent_at) > self.tolerance.as_secs() {
+            return Err(WebhookError::TimestampOutOfTolerance);
+        }
+
+        let mac = self.mac(id, timestamp, body);
+        let valid = signatures
+            .split(' ')
+            .filter_map(|signature| signature.strip_prefix("v1,"))
+            .filter_map(|signature| BASE64_STANDARD.decode(signature).ok())
+            .any(|signature| mac.clone().verify_slice(&signature).is_ok());
+
+        if valid {
+            Ok(())
+        } else {
+            Err(WebhookError::InvalidSignature)
+        }
+    }
+
+    /// Returns the `webhook-signature` header value of a webhook, e.g. to test hook handlers.
+    pub fn sign(&self, id: &str, timestamp: i64, body: &[u8]) -> String {
+        let signature = self
+            .mac(id, &timestamp.to_string(), body)
+            .finalize()
+            .into_bytes();
+        format!("v1,{}", BASE64_STANDARD.encode(signature))
+    }
+
+    fn mac(&self, id: &str, timestamp: &str, body: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(id.as_bytes());
+        mac.update(b".");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid webhook secret, expected `v1,whsec_<base64>`")]
+pub struct InvalidWebhookSecret(#[from] base64::DecodeError);
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("Missing webhook header {0}")]
+    MissingHeader(&'static str),
+    #[error("Invalid webhook timestamp")]
+    InvalidTimestamp,
+    #[error("Webhook timestamp is too old or too new")]
+    TimestampOutOfTolerance,
+    #[error("No valid webhook signature")]
+    InvalidSignature,
+    #[error("Could not read webhook body: {0}")]
+    Body(#[from] BytesRejection),
+    #[error("Invalid webhook payload: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            WebhookError::MissingHeader(_)
+            | WebhookError::InvalidTimestamp
+            | WebhookError::TimestampOutOfTolerance
+            | WebhookError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            WebhookError::Body(_) | WebhookError::Json(_) => StatusCode::BAD_REQUEST,
+        };
+        HookError::new(status, self.to_string()).into_response()
+    }
+}
+
+/// Extracts the JSON payload `P` of a webhook verified by the [`WebhookVerifier`] of the state.
+pub struct Webhook<P>(pub P);
+
+#[async_trait]
+impl<S, P> FromRequest<S> for Webhook<P>
+where
+    S: Send + Sync,
+    P: DeserializeOwned,
+    WebhookVerifier: FromRef<S>,
+{
+    type Rejection = WebhookError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let headers = req.headers().clone();
+        let body = Bytes::from_request(req, state).await?;
+
+        WebhookVerifier::from_ref(state).verify(&headers, &body)?;
+
+        Ok(Webhook(serde_json::from_slice(&body)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example secret of the Standard Webhooks specification.
+    const SECRET: &str = "v1,whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw";
+
+    fn headers(id: &str, timestamp: i64, signature: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(WEBHOOK_ID, id.parse().unwrap());
+        headers.insert(WEBHOOK_TIMESTAMP, timestamp.to_string().parse().unwrap());
+        headers.insert(WEBHOOK_SIGNATURE, signature.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn verifies_signature_and_timestamp() {
+        let verifier = WebhookVerifier::new(SECRET).unwrap();
+        let body = br#"{"user_id":"c0ffee"}"#;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let signature = verifier.sign("msg_1", now, body);
+
+        let valid = headers("msg_1", now, &format!("v1,bm90IGl0 {signature}"));
+        let tampered = headers("msg_2", now, &signature);
+        let stale = headers("msg_1", now - 600, &verifier.sign("msg_1", now - 600, body));
+
+        assert!(verifier.verify(&valid, body).is_ok());
+        assert!(matches!(
+            verifier.verify(&tampered, body),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verifier.verify(&stale, body),
+            Err(WebhookError::TimestampOutOfTolerance)
+        ));
+    }
+}
@@ -1,26 +1,39 @@
 mod auth;
 mod handlers;
+#[cfg(feature = "hooks")]
+pub mod hooks;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod middleware;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
+use crate::api::{ClientBuildError, DynTransport, RetryPolicy};
 use crate::handlers::auth_router;
-use crate::middleware::{AuthState, CookieConfig, Decoder, Empty};
+use crate::middleware::{
+    AuthEvents, AuthState, CookieConfig, Decoder, Empty, DEFAULT_SETTINGS_TTL,
+};
 pub use auth::api;
+pub use auth::circuit_breaker::*;
+pub use auth::dynamic::*;
 pub use auth::service::*;
 pub use auth::types::*;
-pub use auth::{Auth, SessionAuth};
+pub use auth::{AdminAuth, Auth, ClientError, SessionAuth};
 use axum::extract::FromRef;
+use axum::http::HeaderMap;
 use axum::Router;
 use bon::Builder;
-use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
+use url::Url;
 
-pub trait AuthTypes {
-    type AppData: Serialize + for<'de> Deserialize<'de> + Send + Debug;
-    type UserData: Serialize + for<'de> Deserialize<'de> + Send + Debug;
-    type AdditionalData: Serialize + for<'de> Deserialize<'de> + Send + Debug;
+pub trait AuthTypes: Send + Sync + 'static {
+    type AppData: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug;
+    type UserData: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug;
+    type AdditionalData: Serialize + for<'de> Deserialize<'de> + Send + Sync + Debug;
 }
 
 pub struct DefaultAuthTypes;
@@ -43,6 +56,26 @@ pub struct SupabaseAuthConfig {
     pub refresh_cookie_name: String,
     #[builder(into, default = "sb-token-verifier")]
     pub csrf_verifier_cookie_name: String,
+
+    /// How long the GoTrue settings are cached by [`AuthState::settings`].
+    #[builder(default = DEFAULT_SETTINGS_TTL)]
+    pub settings_ttl: Duration,
+
+    /// Retries of idempotent GoTrue requests, e.g. fetching the user or refreshing the session.
+    #[builder(default)]
+    pub retry_policy: RetryPolicy,
+
+    /// Fails GoTrue calls fast while GoTrue is down. Its state is available through
+    /// [`AuthState::circuit_breaker`].
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    /// HTTP transport for GoTrue requests, e.g. a `reqwest::Client` with a proxy or custom root
+    /// certificates. Required without the `reqwest` feature.
+    #[builder(into)]
+    pub transport: Option<DynTransport>,
+    /// Additional headers sent with every GoTrue request, e.g. `X-Client-Info`.
+    #[builder(default)]
+    pub headers: HeaderMap,
 }
 
 #[derive(Clone)]
@@ -58,8 +91,24 @@ where
     T: AuthTypes + Send + Sync + 'static,
 {
     pub fn new(conf: SupabaseAuthConfig) -> Result<Self, SupabaseAuthError> {
-        let service = AuthService::new(conf.api_url, &conf.api_key);
+        let service = AuthService::builder()
+            .url(conf.api_url.clone())
+            .api_key(&conf.api_key)
+            .maybe_transport(conf.transport.clone())
+            .headers(conf.headers.clone())
+            .retry_policy(conf.retry_policy.clone())
+            .maybe_circuit_breaker(conf.circuit_breaker.clone())
+            .build()?;
 
+        Self::with_auth(conf, service)
+    }
+
+    /// Like [`SupabaseAuth::new`], but uses `auth` instead of an [`AuthService`] for `api_url`,
+    /// e.g. a `MockAuth` from the `test-util` feature in tests.
+    pub fn with_auth(
+        conf: SupabaseAuthConfig,
+        auth: impl Auth<T>,
+    ) -> Result<Self, SupabaseAuthError> {
         let decoder = Arc::new(Decoder::new(&conf.jwt_secret));
 
         let cookies = CookieConfig::builder()
@@ -68,11 +117,17 @@ where
             .refresh_cookie_name(conf.refresh_cookie_name)
             .build();
 
-        let state = AuthState::new(service, decoder, cookies);
+        let state = AuthState::new(auth, decoder, cookies).with_settings_ttl(conf.settings_ttl);
 
         Ok(Self { state })
     }
 
+    /// Sets the hooks the handlers of [`SupabaseAuth::router`] invoke, e.g. for an audit log.
+    pub fn with_events(mut self, events: impl AuthEvents<T>) -> Self {
+        self.state = self.state.with_events(events);
+        self
+    }
+
     pub fn router<S>(&self) -> Router<S>
     where
         S: Clone + Send + Sync + 'static,
@@ -87,4 +142,7 @@ where
 }
 
 #[derive(Error, Debug)]
-pub enum SupabaseAuthError {}
+pub enum SupabaseAuthError {
+    #[error(transparent)]
+    Client(#[from] ClientBuildError),
+}
@@ -0,0 +1,149 @@
+//! Metrics recorded through the `metrics` crate facade with the `metrics` feature. Install an
+//! exporter, e.g. `metrics-exporter-prometheus`, to collect them.
+
+use crate::api::{ApiError, ApiErrorCode, OAuthErrorCode};
+use crate::ClientError;
+use axum::body::Bytes;
+use axum::http::{Method, Response};
+use jsonwebtoken::errors::ErrorKind;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Counter of GoTrue API calls, labeled with `method`, `endpoint`, `status` and `error_code`.
+/// Retried requests count once.
+pub const API_REQUESTS: &str = "supabase_auth_api_requests_total";
+/// Histogram of the GoTrue API call latency in seconds including retries, labeled with
+/// `method`, `endpoint` and `status`.
+pub const API_REQUEST_DURATION: &str = "supabase_auth_api_request_duration_seconds";
+/// Counter of decoded JWTs, labeled with `outcome`: `valid`, `expired`, `bad_signature`,
+/// `bad_audience` or `invalid`.
+pub const TOKEN_VALIDATIONS: &str = "supabase_auth_token_validations_total";
+/// Counter of session refreshes, labeled with `outcome`: `success`, `rate_limited`,
+/// `unavailable` or `error`.
+pub const REFRESHES: &str = "supabase_auth_refreshes_total";
+
+pub(crate) fn record_api_call(
+    method: &Method,
+    endpoint: &str,
+    started: Instant,
+    result: &Result<Response<Bytes>, ApiError>,
+) {
+    let endpoint = endpoint_label(endpoint);
+    let status = match result {
+        Ok(response) => response.status().as_str().to_string(),
+        Err(ApiError::Request(status, _, _))
+        | Err(ApiError::OAuth(status, _, _))
+        | Err(ApiError::UnknownHTTP(status)) => status.as_str().to_string(),
+        Err(ApiError::RateLimited { .. }) => "429".to_string(),
+        Err(_) => "none".to_string(),
+    };
+    let error_code = match result {
+        Ok(_) => "none".to_string(),
+        Err(e) => error_code(e),
+    };
+
+    ::metrics::histogram!(
+        API_REQUEST_DURATION,
+        "method" => method.to_string(),
+        "endpoint" => endpoint.clone(),
+        "status" => status.clone(),
+    )
+    .record(started.elapsed());
+    ::metrics::counter!(
+        API_REQUESTS,
+        "method" => method.to_string(),
+        "endpoint" => endpoint,
+        "status" => status,
+        "error_code" => error_code,
+    )
+    .increment(1);
+}
+
+pub(crate) fn record_token_validation<C>(result: &Result<C, jsonwebtoken::errors::Error>) {
+    let outcome = match result.as_ref().map_err(|e| e.kind()) {
+        Ok(_) => "valid",
+        Err(ErrorKind::ExpiredSignature) => "expired",
+        Err(ErrorKind::InvalidSignature) => "bad_signature",
+        Err(ErrorKind::InvalidAudience) => "bad_audience",
+        Err(_) => "invalid",
+    };
+
+    ::metrics::counter!(TOKEN_VALIDATIONS, "outcome" => outcome).increment(1);
+}
+
+pub(crate) fn record_refresh<S>(result: &Result<S, ClientError>) {
+    let outcome = match result {
+        Ok(_) => "success",
+        Err(ClientError::RateLimited { .. }) => "rate_limited",
+        Err(ClientError::Unavailable) => "unavailable",
+        Err(_) => "error",
+    };
+
+    ::metrics::counter!(REFRESHES, "outcome" => outcome).increment(1);
+}
+
+/// Replaces ids in `endpoint` to keep the label cardinality low, e.g. `admin/users/:id`.
+fn endpoint_label(endpoint: &str) -> String {
+    endpoint
+        .split('/')
+        .map(|segment| match Uuid::parse_str(segment) {
+            Ok(_) => ":id",
+            Err(_) => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn error_code(error: &ApiError) -> String {
+    match error {
+        ApiError::Request(_, ApiErrorCode::Unknown(code), _)
+        | ApiError::RateLimited {
+            code: ApiErrorCode::Unknown(code),
+            ..
+        }
+        | ApiError::OAuth(_, OAuthErrorCode::Unknown(code), _) => code.clone(),
+        ApiError::Request(_, code, _) | ApiError::RateLimited { code, .. } => {
+            snake_case(&format!("{code:?}"))
+        }
+        ApiError::OAuth(_, code, _) => snake_case(&format!("{code:?}")),
+        ApiError::UnknownHTTP(_) => "unknown".to_string(),
+        ApiError::Transport(_) => "transport".to_string(),
+        ApiError::InvalidRequest(_) | ApiError::UrlError(_) => "invalid_request".to_string(),
+        ApiError::Json(_) => "invalid_json".to_string(),
+    }
+}
+
+/// Converts a `Debug` formatted variant name, e.g. `InvalidCredentials`, to the error code
+/// GoTrue sends, e.g. `invalid_credentials`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn labels_are_low_cardinality() {
+        let error = ApiError::Request(
+            StatusCode::BAD_REQUEST,
+            ApiErrorCode::InvalidCredentials,
+            String::new(),
+        );
+
+        assert_eq!(
+            endpoint_label("admin/users/0b4e4b6e-34bb-4d43-b6c4-bd3e1c4f0e0a"),
+            "admin/users/:id"
+        );
+        assert_eq!(error_code(&error), "invalid_credentials");
+    }
+}
@@ -33,8 +33,14 @@ where
     }
 
     pub fn decode(&self, token: &str) -> Result<AuthClaims<T>, jsonwebtoken::errors::Error> {
-        jsonwebtoken::decode::<AuthClaims<T>>(token, &self.keys.decoding, &self.validation)
-            .map(|data| data.claims)
+        let result =
+            jsonwebtoken::decode::<AuthClaims<T>>(token, &self.keys.decoding, &self.validation)
+                .map(|data| data.claims);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_token_validation(&result);
+
+        result
     }
 }
 
@@ -104,5 +110,6 @@ mod tests {
         let claims = decoder.decode(JWT).unwrap();
 
         assert_eq!(claims.app_metadata.provider, "email");
+        assert!(!claims.is_anonymous);
     }
 }
@@ -0,0 +1,242 @@
+use super::AuthClaims;
+use crate::api::IdTokenProvider;
+use crate::auth::ClientError;
+use crate::{AuthTypes, DefaultAuthTypes, LogoutScope, User};
+use axum::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::header;
+use axum::http::request::Parts;
+use futures_util::future::BoxFuture;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Hooks into the sign-in lifecycle of the handlers of [`SupabaseAuth::router`], e.g. for an
+/// audit log. Register it with [`SupabaseAuth::with_events`] or
+/// [`AuthState::with_events`](super::AuthState::with_events).
+///
+/// All hooks do nothing by default. They are awaited before the handler responds, so slow work
+/// should be spawned.
+///
+/// [`SupabaseAuth::router`]: crate::SupabaseAuth::router
+/// [`SupabaseAuth::with_events`]: crate::SupabaseAuth::with_events
+pub trait AuthEvents<T: AuthTypes = DefaultAuthTypes>: Send + Sync + 'static {
+    /// A user signed in, or signed up and received a session right away.
+    fn signed_in(
+        &self,
+        user: &User<T>,
+        method: &AuthMethod,
+        request: &RequestMeta,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (user, method, request);
+        async {}
+    }
+
+    /// Signing in or signing up failed. `email` is the one entered, if the method uses one.
+    fn sign_in_failed(
+        &self,
+        method: &AuthMethod,
+        email: Option<&str>,
+        error: &ClientError,
+        request: &RequestMeta,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (method, email, error, request);
+        async {}
+    }
+
+    /// A user signed out. Not invoked if the access token no longer decodes, e.g. it expired.
+    fn signed_out(
+        &self,
+        claims: &AuthClaims<T>,
+        scope: LogoutScope,
+        request: &RequestMeta,
+    ) -> impl Future<Output = ()> + Send {
+        let _ = (claims, scope, request);
+        async {}
+    }
+
+    /// A session was refreshed through `POST /refresh`.
+    fn refreshed(&self, user: &User<T>, request: &RequestMeta) -> impl Future<Output = ()> + Send {
+        let _ = (user, request);
+        async {}
+    }
+}
+
+/// No hooks, the default of [`AuthState`](super::AuthState).
+impl<T: AuthTypes> AuthEvents<T> for () {}
+
+/// How a user signed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthMethod {
+    Password,
+    Anonymous,
+    /// Natively obtained OIDC ID token.
+    IdToken(IdTokenProvider),
+    /// OAuth or SSO redirect, with the provider the user signed in with. The provider is
+    /// unknown if exchanging the code failed.
+    OAuth(Option<String>),
+    /// Magic link or one-time password.
+    Otp,
+}
+
+impl AuthMethod {
+    /// Method of a completed PKCE flow, which depending on `provider` was a magic link or an
+    /// OAuth or SSO redirect.
+    pub(crate) fn from_pkce_provider(provider: &str) -> Self {
+        match provider {
+            "email" | "phone" => AuthMethod::Otp,
+            provider => AuthMethod::OAuth(Some(provider.to_string())),
+        }
+    }
+}
+
+/// Metadata of the request an event happened in.
+///
+/// `peer_addr` is only known if the router is served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`. Behind a proxy, `forwarded_for` holds
+/// the `X-Forwarded-For` header, which is only trustworthy if the proxy sets it.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMeta {
+    pub peer_addr: Option<SocketAddr>,
+    pub forwarded_for: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestMeta
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Ok(RequestMeta {
+            peer_addr: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| *addr),
+            forwarded_for: header("x-forwarded-for"),
+            user_agent: header(header::USER_AGENT.as_str()),
+        })
+    }
+}
+
+/// Type-erased [`AuthEvents`] implementation held by [`AuthState`](super::AuthState).
+pub struct DynAuthEvents<T: AuthTypes = DefaultAuthTypes> {
+    inner: Arc<dyn ErasedAuthEvents<T>>,
+}
+
+impl<T: AuthTypes> DynAuthEvents<T> {
+    pub fn new(events: impl AuthEvents<T>) -> Self {
+        Self {
+            inner: Arc::new(events),
+        }
+    }
+}
+
+impl<T: AuthTypes> Clone for DynAuthEvents<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Object-safe counterpart of [`AuthEvents`].
+trait ErasedAuthEvents<T: AuthTypes>: Send + Sync {
+    fn signed_in<'a>(
+        &'a self,
+        user: &'a User<T>,
+        method: &'a AuthMethod,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()>;
+
+    fn sign_in_failed<'a>(
+        &'a self,
+        method: &'a AuthMethod,
+        email: Option<&'a str>,
+        error: &'a ClientError,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()>;
+
+    fn signed_out<'a>(
+        &'a self,
+        claims: &'a AuthClaims<T>,
+        scope: LogoutScope,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()>;
+
+    fn refreshed<'a>(&'a self, user: &'a User<T>, request: &'a RequestMeta) -> BoxFuture<'a, ()>;
+}
+
+impl<T: AuthTypes, E: AuthEvents<T>> ErasedAuthEvents<T> for E {
+    fn signed_in<'a>(
+        &'a self,
+        user: &'a User<T>,
+        method: &'a AuthMethod,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(AuthEvents::signed_in(self, user, method, request))
+    }
+
+    fn sign_in_failed<'a>(
+        &'a self,
+        method: &'a AuthMethod,
+        email: Option<&'a str>,
+        error: &'a ClientError,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(AuthEvents::sign_in_failed(
+            self, method, email, error, request,
+        ))
+    }
+
+    fn signed_out<'a>(
+        &'a self,
+        claims: &'a AuthClaims<T>,
+        scope: LogoutScope,
+        request: &'a RequestMeta,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(AuthEvents::signed_out(self, claims, scope, request))
+    }
+
+    fn refreshed<'a>(&'a self, user: &'a User<T>, request: &'a RequestMeta) -> BoxFuture<'a, ()> {
+        Box::pin(AuthEvents::refreshed(self, user, request))
+    }
+}
+
+impl<T: AuthTypes> AuthEvents<T> for DynAuthEvents<T> {
+    async fn signed_in(&self, user: &User<T>, method: &AuthMethod, request: &RequestMeta) {
+        self.inner.signed_in(user, method, request).await
+    }
+
+    async fn sign_in_failed(
+        &self,
+        method: &AuthMethod,
+        email: Option<&str>,
+        error: &ClientError,
+        request: &RequestMeta,
+    ) {
+        self.inner
+            .sign_in_failed(method, email, error, request)
+            .await
+    }
+
+    async fn signed_out(&self, claims: &AuthClaims<T>, scope: LogoutScope, request: &RequestMeta) {
+        self.inner.signed_out(claims, scope, request).await
+    }
+
+    async fn refreshed(&self, user: &User<T>, request: &RequestMeta) {
+        self.inner.refreshed(user, request).await
+    }
+}
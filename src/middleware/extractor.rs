@@ -3,7 +3,7 @@ use crate::auth::types;
 use crate::AuthTypes;
 use axum::extract::{FromRef, FromRequestParts};
 use axum::http::request::Parts;
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::{async_trait, Json};
 use axum_extra::extract::CookieJar;
@@ -16,6 +16,8 @@ pub type AuthClaims<T> =
     Claims<<T as AuthTypes>::AppData, <T as AuthTypes>::UserData, <T as AuthTypes>::AdditionalData>;
 
 pub struct User<T: AuthTypes>(pub AuthClaims<T>);
+/// A signed in user that did not sign in anonymously. Anonymous users are rejected.
+pub struct PermanentUser<T: AuthTypes>(pub AuthClaims<T>);
 pub struct MaybeUser<T: AuthTypes>(pub Option<AuthClaims<T>>);
 pub struct SomeAccessToken<T: AuthTypes>(pub AccessToken<T>);
 
@@ -75,6 +77,25 @@ where
     }
 }
 
+#[async_trait]
+impl<S, T> FromRequestParts<S> for PermanentUser<T>
+where
+    S: Send + Sync,
+    T: AuthTypes,
+    User<T>: FromRequestParts<S, Rejection = AuthError>,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let User(claims) = User::from_request_parts(parts, state).await?;
+        if claims.is_anonymous {
+            return Err(AuthError::AnonymousUser);
+        }
+
+        Ok(PermanentUser(claims))
+    }
+}
+
 #[async_trait]
 impl<S, T> FromRequestParts<S> for MaybeUser<T>
 where
@@ -92,16 +113,15 @@ where
 
         let state = AuthState::<T>::from_ref(state);
 
-        let token = jar.get(state.cookies().auth_cookie_name());
-        let token = match token {
+        let token = match access_token(&jar, parts, &state) {
             Some(token) => token,
             None => {
-                trace!("no auth cookie found");
+                trace!("no auth cookie or bearer token found");
                 return Ok(MaybeUser(None));
             }
         };
 
-        let claims = state.decode(token.value_trimmed()).map_err(|error| {
+        let claims = state.decode(token).map_err(|error| {
             warn!(error = ?error, "invalid token");
             AuthError::InvalidToken
         })?;
@@ -130,15 +150,14 @@ where
 
         let state = AuthState::from_ref(state);
 
-        let token = jar.get(state.cookies().auth_cookie_name());
-        let token = match token {
+        let token = match access_token(&jar, parts, &state) {
             Some(token) => token,
             None => {
-                trace!("no auth cookie found");
+                trace!("no auth cookie or bearer token found");
                 return Err(AuthError::MissingCredentials);
             }
         };
-        let token = AccessToken::new(token.value_trimmed());
+        let token = AccessToken::new(token);
 
         Ok(SomeAccessToken(token))
     }
@@ -161,20 +180,37 @@ where
 
         let state = AuthState::from_ref(state);
 
-        let token = jar.get(state.cookies().auth_cookie_name());
-        let token = match token {
+        let token = match access_token(&jar, parts, &state) {
             Some(token) => token,
             None => {
-                trace!("no auth cookie found");
+                trace!("no auth cookie or bearer token found");
                 return Err(AuthError::MissingCredentials);
             }
         };
-        let token = AccessToken::new(token.value_trimmed());
+        let token = AccessToken::new(token);
 
         Ok(token)
     }
 }
 
+/// Returns the access token of the auth cookie or, if there is none, of an
+/// `Authorization: Bearer` header.
+fn access_token<'a, T: AuthTypes>(
+    jar: &'a CookieJar,
+    parts: &'a Parts,
+    state: &AuthState<T>,
+) -> Option<&'a str> {
+    if let Some(cookie) = jar.get(state.cookies().auth_cookie_name()) {
+        return Some(cookie.value_trimmed());
+    }
+
+    let authorization = parts.headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = authorization.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then_some(token.trim())
+}
+
 // error types for axum errors
 #[derive(Debug)]
 pub enum AuthError {
@@ -182,6 +218,7 @@ pub enum AuthError {
     MissingCredentials,
     TokenCreation,
     InvalidToken,
+    AnonymousUser,
 }
 
 impl IntoResponse for AuthError {
@@ -191,6 +228,7 @@ impl IntoResponse for AuthError {
             AuthError::MissingCredentials => (StatusCode::BAD_REQUEST, "Missing credentials"),
             AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "Token creation error"),
             AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
+            AuthError::AnonymousUser => (StatusCode::FORBIDDEN, "Anonymous user"),
         };
         let body = Json(json!({ "error": error_message }));
         (status, body).into_response()
@@ -1,11 +1,13 @@
 mod decoder;
+mod events;
 mod extractor;
 mod state;
 
 pub use decoder::*;
+pub use events::*;
 pub use extractor::*;
 use serde::{Deserialize, Serialize};
-pub use state::{AuthState, CookieConfig};
+pub use state::{AuthState, CookieConfig, DEFAULT_SETTINGS_TTL};
 use std::fmt::Debug;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,13 +24,17 @@ where
     pub role: String,
     pub app_metadata: AppMetadata<A>,
     pub user_metadata: U,
+    #[serde(default)]
+    pub is_anonymous: bool,
     #[serde(flatten)]
     pub additional: T,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AppMetadata<A> {
+    #[serde(default)]
     pub provider: String,
+    #[serde(default)]
     pub providers: Vec<String>,
     #[serde(flatten)]
     pub additional: A,
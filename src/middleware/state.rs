@@ -1,8 +1,20 @@
-use crate::middleware::{AuthClaims, Decoder};
-use crate::{AuthService, AuthTypes};
+use crate::api::Settings;
+use crate::auth::ClientError;
+use crate::middleware::{AuthClaims, AuthEvents, Decoder, DynAuthEvents};
+use crate::{Auth, AuthTypes, CircuitBreaker, DynAuth};
 use axum::extract::FromRef;
 use bon::Builder;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Default time the GoTrue settings are cached for.
+pub const DEFAULT_SETTINGS_TTL: Duration = Duration::from_secs(60);
+
+/// Time a failed settings fetch is cached for, before GoTrue is asked again.
+const SETTINGS_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
 #[derive(Builder, Clone)]
 pub struct CookieConfig {
@@ -29,24 +41,104 @@ pub struct AuthState<T>
 where
     T: AuthTypes,
 {
-    auth: AuthService,
+    auth: DynAuth<T>,
     decoder: Arc<Decoder<T>>,
     cookies: CookieConfig,
+    settings: Arc<SettingsCache>,
+    events: DynAuthEvents<T>,
+}
+
+struct SettingsCache {
+    ttl: Duration,
+    /// Held while fetching, so concurrent callers wait for a single fetch.
+    cached: Mutex<CachedSettings>,
+}
+
+#[derive(Default)]
+struct CachedSettings {
+    settings: Option<(Instant, Settings)>,
+    failed_at: Option<Instant>,
+}
+
+impl SettingsCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cached: Mutex::default(),
+        }
+    }
+
+    /// Returns the cached settings or fetches them. While fetching fails, the stale settings
+    /// are returned if there are any, and GoTrue is asked at most once per retry interval.
+    async fn get_or_fetch(
+        &self,
+        fetch: impl Future<Output = Result<Settings, ClientError>>,
+    ) -> Result<Settings, ClientError> {
+        let mut cached = self.cached.lock().await;
+
+        let stale = match &cached.settings {
+            Some((fetched_at, settings)) if fetched_at.elapsed() < self.ttl => {
+                return Ok(settings.clone());
+            }
+            Some((_, settings)) => Some(settings.clone()),
+            None => None,
+        };
+        if cached
+            .failed_at
+            .is_some_and(|failed_at| failed_at.elapsed() < SETTINGS_RETRY_INTERVAL)
+        {
+            return stale.ok_or(ClientError::Unavailable);
+        }
+
+        match fetch.await {
+            Ok(settings) => {
+                cached.settings = Some((Instant::now(), settings.clone()));
+                cached.failed_at = None;
+                Ok(settings)
+            }
+            Err(err) => {
+                cached.failed_at = Some(Instant::now());
+                match stale {
+                    Some(settings) => {
+                        warn!(%err, "fetching settings failed, using stale settings");
+                        Ok(settings)
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
 }
 
 impl<T> AuthState<T>
 where
     T: AuthTypes,
 {
-    pub fn new(auth: AuthService, decoder: Arc<Decoder<T>>, cookies: CookieConfig) -> Self {
+    /// Creates the state from any [`Auth`] implementation, usually an
+    /// [`AuthService`](crate::AuthService).
+    pub fn new(auth: impl Auth<T>, decoder: Arc<Decoder<T>>, cookies: CookieConfig) -> Self {
         Self {
             decoder,
-            auth,
+            auth: DynAuth::new(auth),
             cookies,
+            settings: Arc::new(SettingsCache::new(DEFAULT_SETTINGS_TTL)),
+            events: DynAuthEvents::new(()),
         }
     }
 
-    pub fn auth(&self) -> &AuthService {
+    /// Sets how long the GoTrue settings are cached, defaults to [`DEFAULT_SETTINGS_TTL`].
+    pub fn with_settings_ttl(mut self, ttl: Duration) -> Self {
+        self.settings = Arc::new(SettingsCache::new(ttl));
+        self
+    }
+
+    /// Sets the hooks the handlers invoke on sign-in, failed sign-in and sign-out.
+    pub fn with_events(mut self, events: impl AuthEvents<T>) -> Self {
+        self.events = DynAuthEvents::new(events);
+        self
+    }
+
+    pub fn auth(&self) -> &DynAuth<T> {
         &self.auth
     }
 
@@ -58,6 +150,26 @@ where
         &self.cookies
     }
 
+    pub fn events(&self) -> &DynAuthEvents<T> {
+        &self.events
+    }
+
+    /// Returns the circuit breaker of the [`Auth`] implementation, e.g. to only validate JWTs
+    /// locally while GoTrue is down.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.auth.circuit_breaker()
+    }
+
+    /// Returns the GoTrue settings, fetched at most once per TTL. Used to show only enabled
+    /// providers in the login UI.
+    ///
+    /// Concurrent callers share a single fetch. If fetching fails, the last settings are
+    /// returned until GoTrue is asked again after a short retry interval; without any, the
+    /// error is returned and then [`ClientError::Unavailable`] until the retry.
+    pub async fn settings(&self) -> Result<Settings, ClientError> {
+        self.settings.get_or_fetch(self.auth.settings()).await
+    }
+
     pub fn decode(&self, token: &str) -> Result<AuthClaims<T>, jsonwebtoken::errors::Error> {
         self.decoder.decode(token)
     }
@@ -72,6 +184,8 @@ where
             auth: self.auth.clone(),
             decoder: self.decoder.clone(),
             cookies: self.cookies.clone(),
+            settings: self.settings.clone(),
+            events: self.events.clone(),
         }
     }
 }
@@ -85,7 +199,7 @@ where
     }
 }
 
-impl<T> FromRef<AuthState<T>> for AuthService
+impl<T> FromRef<AuthState<T>> for DynAuth<T>
 where
     T: AuthTypes,
 {
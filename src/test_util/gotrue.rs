@@ -0,0 +1,1302 @@
+use crate::api::ApiClient;
+use crate::middleware::AppMetadata;
+use crate::{AccessToken, AuthService, AuthTypes, Identity, RefreshToken, Session, User};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use bon::bon;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use url::Url;
+use uuid::Uuid;
+
+const API_KEY: &str = "fake-api-key";
+/// Lifetime of issued access tokens in seconds.
+const EXPIRES_IN: i32 = 3600;
+const MIN_PASSWORD_LENGTH: usize = 6;
+
+/// In-process fake of the GoTrue REST API, listening on an ephemeral local port.
+///
+/// Implements `signup` (including anonymous sign-ins), the `password`, `refresh_token` and
+/// `pkce` grants of `token`, `user`, `logout`, the `admin/users` API, `otp`, `verify` and
+/// `resend`. No messages are sent, the pending OTP of a user can be read with
+/// [`FakeGoTrue::otp`] instead. `authorize` completes immediately, signing in as
+/// `{provider}-user@example.com` and redirecting with a PKCE auth code.
+///
+/// The server shuts down when dropped.
+pub struct FakeGoTrue {
+    url: Url,
+    state: Arc<FakeState>,
+    server: JoinHandle<()>,
+}
+
+#[bon]
+impl FakeGoTrue {
+    /// Starts the fake server. With `autoconfirm` users are confirmed on signup, with
+    /// `signup_disabled` new users can only be created through the admin API.
+    #[builder(finish_fn = start)]
+    pub async fn new(
+        #[builder(default)] autoconfirm: bool,
+        #[builder(default)] signup_disabled: bool,
+        #[builder(into, default = "secret")] jwt_secret: String,
+        #[builder(into, default = "authenticated")] aud: String,
+        /// Redirect target of `authorize` when no `redirect_to` is given.
+        #[builder(into, default = "http://localhost:3000")]
+        site_url: String,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("could not bind fake GoTrue");
+        let addr = listener
+            .local_addr()
+            .expect("fake GoTrue has no local address");
+        let url = Url::parse(&format!("http://{addr}/")).expect("invalid fake GoTrue URL");
+
+        let state = Arc::new(FakeState {
+            autoconfirm,
+            signup_disabled,
+            jwt_secret,
+            aud,
+            site_url,
+            store: Mutex::default(),
+        });
+
+        let router = router(state.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, router)
+                .await
+                .expect("fake GoTrue failed");
+        });
+
+        Self { url, state, server }
+    }
+}
+
+impl FakeGoTrue {
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn api_key(&self) -> &str {
+        API_KEY
+    }
+
+    pub fn jwt_secret(&self) -> &str {
+        &self.state.jwt_secret
+    }
+
+    pub fn api_client<T: AuthTypes>(&self) -> ApiClient<T> {
+        ApiClient::new(self.url.clone(), Duration::from_secs(5), API_KEY)
+    }
+
+    pub fn auth_service<T: AuthTypes>(&self) -> AuthService<T> {
+        AuthService::new(self.url.clone(), API_KEY)
+    }
+
+    /// Returns a `service_role` JWT accepted by the admin API.
+    pub fn service_role_key(&self) -> String {
+        self.state.encode(&json!({
+            "iss": "fake-gotrue",
+            "role": "service_role",
+            "exp": 9999999999u64,
+        }))
+    }
+
+    /// Returns the pending OTP of the user with the given email or phone, as it would have been
+    /// sent by GoTrue.
+    pub fn otp(&self, email_or_phone: &str) -> Option<String> {
+        let store = self.state.store();
+        let user_id = store.find(Some(email_or_phone), Some(email_or_phone))?;
+        store.otps.get(&user_id).map(|otp| otp.token.clone())
+    }
+
+    /// Returns the token hash of the pending OTP, as used in email links.
+    pub fn otp_token_hash(&self, email_or_phone: &str) -> Option<String> {
+        let store = self.state.store();
+        let user_id = store.find(Some(email_or_phone), Some(email_or_phone))?;
+        store.otps.get(&user_id).map(|otp| otp.token_hash.clone())
+    }
+}
+
+impl Drop for FakeGoTrue {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn router(state: Arc<FakeState>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/settings", get(settings))
+        .route("/signup", post(signup))
+        .route("/token", post(token))
+        .route("/user", get(get_user).put(update_user))
+        .route("/logout", post(logout))
+        .route("/otp", post(otp))
+        .route("/verify", post(verify))
+        .route("/resend", post(resend))
+        .route("/authorize", get(authorize))
+        .route(
+            "/admin/users",
+            get(admin_list_users).post(admin_create_user),
+        )
+        .route(
+            "/admin/users/:id",
+            get(admin_get_user)
+                .put(admin_update_user)
+                .delete(admin_delete_user),
+        )
+        .with_state(state)
+}
+
+struct FakeState {
+    autoconfirm: bool,
+    signup_disabled: bool,
+    jwt_secret: String,
+    aud: String,
+    site_url: String,
+    store: Mutex<Store>,
+}
+
+#[derive(Default)]
+struct Store {
+    users: Vec<StoredUser>,
+    /// User id by session id.
+    sessions: HashMap<Uuid, Uuid>,
+    /// Session id by refresh token.
+    refresh_tokens: HashMap<String, Uuid>,
+    /// Pending OTP by user id.
+    otps: HashMap<Uuid, Otp>,
+    /// PKCE flows by auth code.
+    flows: HashMap<String, Flow>,
+}
+
+struct StoredUser {
+    user: User,
+    password: Option<String>,
+}
+
+struct Otp {
+    kind: String,
+    token: String,
+    token_hash: String,
+}
+
+struct Flow {
+    user_id: Uuid,
+    code_challenge: String,
+}
+
+#[derive(Deserialize)]
+struct TokenClaims {
+    role: String,
+    #[serde(default)]
+    session_id: Option<Uuid>,
+}
+
+/// Error response in the format of GoTrue's API or its OAuth token endpoint.
+enum FakeError {
+    Api(StatusCode, &'static str, String),
+    OAuth(String),
+}
+
+impl IntoResponse for FakeError {
+    fn into_response(self) -> Response {
+        match self {
+            FakeError::Api(status, error_code, msg) => {
+                let body = json!({
+                    "code": status.as_u16(),
+                    "error_code": error_code,
+                    "msg": msg,
+                });
+
+                (status, Json(body)).into_response()
+            }
+            FakeError::OAuth(description) => {
+                let body = json!({
+                    "error": "invalid_grant",
+                    "error_description": description,
+                });
+
+                (StatusCode::BAD_REQUEST, Json(body)).into_response()
+            }
+        }
+    }
+}
+
+type HandlerResult = Result<Response, FakeError>;
+
+fn api_error(status: StatusCode, error_code: &'static str, msg: &str) -> FakeError {
+    FakeError::Api(status, error_code, msg.to_string())
+}
+
+fn oauth_error(description: &str) -> FakeError {
+    FakeError::OAuth(description.to_string())
+}
+
+fn user_not_found() -> FakeError {
+    api_error(StatusCode::NOT_FOUND, "user_not_found", "User not found")
+}
+
+fn check_password(password: &str) -> Result<(), FakeError> {
+    if password.len() < MIN_PASSWORD_LENGTH {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "weak_password",
+            "Password should be at least 6 characters.",
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_email(email: &str) -> Result<(), FakeError> {
+    if !email.contains('@') {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "email_address_invalid",
+            &format!("Email address \"{email}\" is invalid"),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Merges the top-level keys of `update` into `target`, like GoTrue does for metadata.
+fn merge(target: &mut Value, update: Value) {
+    match (target.as_object_mut(), update) {
+        (Some(target), Value::Object(update)) => target.extend(update),
+        (_, update) => *target = update,
+    }
+}
+
+/// Parses Go duration strings such as `24h` or `1h30m`.
+fn parse_duration(value: &str) -> Option<time::Duration> {
+    let mut total = time::Duration::ZERO;
+    let mut rest = value;
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        total += match &rest[..unit] {
+            "h" => time::Duration::hours(amount),
+            "m" => time::Duration::minutes(amount),
+            "s" => time::Duration::seconds(amount),
+            "ms" => time::Duration::milliseconds(amount),
+            _ => return None,
+        };
+        rest = &rest[unit..];
+    }
+
+    Some(total)
+}
+
+fn confirm_email(user: &mut User) {
+    let now = OffsetDateTime::now_utc();
+    user.email_confirmed_at = Some(now);
+    user.confirmed_at = Some(now);
+}
+
+fn confirm_phone(user: &mut User) {
+    let now = OffsetDateTime::now_utc();
+    user.phone_confirmed_at = Some(now);
+    user.confirmed_at = Some(now);
+}
+
+impl Store {
+    fn user(&self, id: Uuid) -> Option<&StoredUser> {
+        self.users.iter().find(|stored| stored.user.id == id)
+    }
+
+    fn user_mut(&mut self, id: Uuid) -> Option<&mut StoredUser> {
+        self.users.iter_mut().find(|stored| stored.user.id == id)
+    }
+
+    fn find(&self, email: Option<&str>, phone: Option<&str>) -> Option<Uuid> {
+        self.users
+            .iter()
+            .find(|stored| {
+                let user = &stored.user;
+                email.is_some_and(|email| {
+                    !user.email.is_empty() && user.email.eq_ignore_ascii_case(email)
+                }) || phone.is_some_and(|phone| !user.phone.is_empty() && user.phone == phone)
+            })
+            .map(|stored| stored.user.id)
+    }
+
+    fn insert(&mut self, user: User, password: Option<String>) -> Uuid {
+        let id = user.id;
+        self.users.push(StoredUser { user, password });
+        id
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        self.users.retain(|stored| stored.user.id != id);
+        self.otps.remove(&id);
+        self.revoke_sessions(id, None);
+    }
+
+    fn send_otp(&mut self, user_id: Uuid, kind: &str) {
+        let token = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+        let otp = Otp {
+            kind: kind.to_string(),
+            token,
+            token_hash: Uuid::new_v4().simple().to_string(),
+        };
+
+        let now = OffsetDateTime::now_utc();
+        if let Some(stored) = self.user_mut(user_id) {
+            match kind {
+                "signup" => stored.user.confirmation_sent_at = Some(now),
+                "recovery" => stored.user.recovery_sent_at = Some(now),
+                "email_change" => stored.user.email_change_sent_at = Some(now),
+                "phone_change" => stored.user.phone_change_sent_at = Some(now),
+                _ => {}
+            }
+        }
+
+        self.otps.insert(user_id, otp);
+    }
+
+    /// Revokes all sessions of the user except `keep`.
+    fn revoke_sessions(&mut self, user_id: Uuid, keep: Option<Uuid>) {
+        self.sessions
+            .retain(|session_id, owner| *owner != user_id || Some(*session_id) == keep);
+        let sessions = &self.sessions;
+        self.refresh_tokens
+            .retain(|_, session_id| sessions.contains_key(session_id));
+    }
+}
+
+impl FakeState {
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().expect("fake GoTrue store poisoned")
+    }
+
+    fn encode(&self, claims: &Value) -> String {
+        jsonwebtoken::encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
+        )
+        .expect("could not encode JWT")
+    }
+
+    fn decode(&self, headers: &HeaderMap) -> Result<TokenClaims, FakeError> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                api_error(
+                    StatusCode::UNAUTHORIZED,
+                    "no_authorization",
+                    "This endpoint requires a Bearer token",
+                )
+            })?;
+
+        let mut validation = Validation::default();
+        validation.validate_aud = false;
+
+        jsonwebtoken::decode::<TokenClaims>(
+            token,
+            &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
+            &validation,
+        )
+        .map(|data| data.claims)
+        .map_err(|e| api_error(StatusCode::FORBIDDEN, "bad_jwt", &e.to_string()))
+    }
+
+    /// Returns user and session id of the bearer token's session.
+    fn authenticate(&self, store: &Store, headers: &HeaderMap) -> Result<(Uuid, Uuid), FakeError> {
+        let claims = self.decode(headers)?;
+
+        claims
+            .session_id
+            .and_then(|session_id| Some((*store.sessions.get(&session_id)?, session_id)))
+            .ok_or_else(|| {
+                api_error(
+                    StatusCode::FORBIDDEN,
+                    "session_not_found",
+                    "Session from session_id claim in JWT does not exist",
+                )
+            })
+    }
+
+    fn authorize_admin(&self, headers: &HeaderMap) -> Result<(), FakeError> {
+        match self.decode(headers)?.role.as_str() {
+            "service_role" | "supabase_admin" => Ok(()),
+            _ => Err(api_error(
+                StatusCode::FORBIDDEN,
+                "not_admin",
+                "User not allowed",
+            )),
+        }
+    }
+
+    fn new_user(
+        &self,
+        email: Option<String>,
+        phone: Option<String>,
+        provider: &str,
+        user_metadata: Value,
+    ) -> User {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+        let identity = Identity {
+            identity_id: Uuid::new_v4(),
+            id: id.to_string(),
+            user_id: id,
+            identity_data: json!({ "sub": id, "email": email, "phone": phone }),
+            provider: provider.to_string(),
+            email: email.clone(),
+            last_sign_in_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        User {
+            id,
+            aud: self.aud.clone(),
+            role: "authenticated".to_string(),
+            email: email.unwrap_or_default(),
+            email_confirmed_at: None,
+            phone: phone.unwrap_or_default(),
+            phone_confirmed_at: None,
+            confirmed_at: None,
+            confirmation_sent_at: None,
+            invited_at: None,
+            recovery_sent_at: None,
+            new_email: None,
+            email_change_sent_at: None,
+            new_phone: None,
+            phone_change_sent_at: None,
+            last_sign_in_at: None,
+            app_metadata: AppMetadata {
+                provider: provider.to_string(),
+                providers: vec![provider.to_string()],
+                additional: json!({}),
+            },
+            user_metadata,
+            identities: vec![identity],
+            factors: Vec::new(),
+            is_anonymous: false,
+            banned_until: None,
+            deleted_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Creates a new session for the user.
+    fn sign_in(&self, store: &mut Store, user_id: Uuid) -> Result<Session, FakeError> {
+        let stored = store.user(user_id).ok_or_else(user_not_found)?;
+        if stored.user.is_banned() {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "user_banned",
+                "User is banned",
+            ));
+        }
+
+        let session_id = Uuid::new_v4();
+        store.sessions.insert(session_id, user_id);
+
+        Ok(self.issue_tokens(store, user_id, session_id))
+    }
+
+    /// Issues a new access and refresh token for an existing session.
+    fn issue_tokens(&self, store: &mut Store, user_id: Uuid, session_id: Uuid) -> Session {
+        let now = OffsetDateTime::now_utc();
+        let refresh_token = Uuid::new_v4().simple().to_string();
+        store
+            .refresh_tokens
+            .insert(refresh_token.clone(), session_id);
+
+        let stored = store.user_mut(user_id).expect("session of unknown user");
+        stored.user.last_sign_in_at = Some(now);
+        let user = stored.user.clone();
+
+        let expires_at = now + time::Duration::seconds(EXPIRES_IN.into());
+        let access_token = self.encode(&json!({
+            "aud": self.aud,
+            "exp": expires_at.unix_timestamp(),
+            "iat": now.unix_timestamp(),
+            "sub": user.id,
+            "email": user.email,
+            "phone": user.phone,
+            "role": user.role,
+            "app_metadata": user.app_metadata,
+            "user_metadata": user.user_metadata,
+            "is_anonymous": user.is_anonymous,
+            "session_id": session_id,
+        }));
+
+        Session {
+            access_token: AccessToken::from(access_token),
+            token_type: "bearer".to_string(),
+            expires_in: EXPIRES_IN,
+            expires_at,
+            refresh_token: RefreshToken::from(refresh_token),
+            user,
+        }
+    }
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({
+        "version": "fake",
+        "name": "GoTrue",
+        "description": "In-process fake GoTrue",
+    }))
+}
+
+async fn settings(State(state): State<Arc<FakeState>>) -> Json<Value> {
+    Json(json!({
+        "external": {
+            "anonymous_users": !state.signup_disabled,
+            "email": true,
+            "phone": true,
+            "github": true,
+        },
+        "disable_signup": state.signup_disabled,
+        "mailer_autoconfirm": state.autoconfirm,
+        "phone_autoconfirm": state.autoconfirm,
+        "sms_provider": "",
+    }))
+}
+
+#[derive(Deserialize)]
+struct SignupBody {
+    email: Option<String>,
+    phone: Option<String>,
+    password: Option<String>,
+    data: Option<Value>,
+}
+
+async fn signup(
+    State(state): State<Arc<FakeState>>,
+    Json(body): Json<SignupBody>,
+) -> HandlerResult {
+    if state.signup_disabled {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "signup_disabled",
+            "Signups not allowed for this instance",
+        ));
+    }
+
+    let mut store = state.store();
+    let data = body.data.unwrap_or_else(|| json!({}));
+
+    if body.email.is_none() && body.phone.is_none() {
+        let mut user = state.new_user(None, None, "anonymous", data);
+        user.is_anonymous = true;
+        user.identities.clear();
+        let user_id = store.insert(user, None);
+
+        return Ok(Json(state.sign_in(&mut store, user_id)?).into_response());
+    }
+
+    let password = body.password.unwrap_or_default();
+    check_password(&password)?;
+
+    let email = body.email.map(|email| email.to_lowercase());
+    if let Some(email) = &email {
+        check_email(email)?;
+    }
+    if let Some(user_id) = store.find(email.as_deref(), body.phone.as_deref()) {
+        if state.autoconfirm {
+            return Err(api_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "user_already_exists",
+                "User already registered",
+            ));
+        }
+
+        let stored = store.user(user_id).ok_or_else(user_not_found)?;
+        if stored.user.confirmed_at.is_some() {
+            // GoTrue responds with an obfuscated user to prevent user enumeration.
+            let user = state.new_user(email, body.phone, "email", json!({}));
+            return Ok(Json(user).into_response());
+        }
+
+        store.send_otp(user_id, "signup");
+        let user = store.user(user_id).ok_or_else(user_not_found)?.user.clone();
+        return Ok(Json(user).into_response());
+    }
+
+    let provider = if email.is_some() { "email" } else { "phone" };
+    let is_email = email.is_some();
+    let mut user = state.new_user(email, body.phone, provider, data);
+    if state.autoconfirm {
+        if is_email {
+            confirm_email(&mut user);
+        } else {
+            confirm_phone(&mut user);
+        }
+    }
+    let user_id = store.insert(user, Some(password));
+
+    if state.autoconfirm {
+        return Ok(Json(state.sign_in(&mut store, user_id)?).into_response());
+    }
+
+    store.send_otp(user_id, if is_email { "signup" } else { "sms" });
+    let user = store.user(user_id).ok_or_else(user_not_found)?.user.clone();
+
+    Ok(Json(user).into_response())
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    grant_type: String,
+}
+
+#[derive(Deserialize)]
+struct TokenBody {
+    email: Option<String>,
+    phone: Option<String>,
+    password: Option<String>,
+    refresh_token: Option<String>,
+    auth_code: Option<String>,
+    code_verifier: Option<String>,
+}
+
+async fn token(
+    State(state): State<Arc<FakeState>>,
+    Query(query): Query<TokenQuery>,
+    Json(body): Json<TokenBody>,
+) -> HandlerResult {
+    let mut store = state.store();
+
+    let session = match query.grant_type.as_str() {
+        "password" => {
+            let invalid = || {
+                api_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_credentials",
+                    "Invalid login credentials",
+                )
+            };
+
+            let user_id = store
+                .find(body.email.as_deref(), body.phone.as_deref())
+                .ok_or_else(invalid)?;
+            let stored = store.user(user_id).ok_or_else(invalid)?;
+            if stored.password.is_none() || stored.password != body.password {
+                return Err(invalid());
+            }
+            if stored.user.confirmed_at.is_none() {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    "email_not_confirmed",
+                    "Email not confirmed",
+                ));
+            }
+
+            state.sign_in(&mut store, user_id)?
+        }
+        "refresh_token" => {
+            let refresh_token = body.refresh_token.unwrap_or_default();
+            let session_id = store
+                .refresh_tokens
+                .remove(&refresh_token)
+                .ok_or_else(|| oauth_error("Invalid Refresh Token: Refresh Token Not Found"))?;
+            let user_id = *store
+                .sessions
+                .get(&session_id)
+                .ok_or_else(|| oauth_error("Invalid Refresh Token: Session Expired"))?;
+
+            state.issue_tokens(&mut store, user_id, session_id)
+        }
+        "pkce" => {
+            let flow = store
+                .flows
+                .remove(&body.auth_code.unwrap_or_default())
+                .ok_or_else(|| {
+                    api_error(
+                        StatusCode::NOT_FOUND,
+                        "flow_state_not_found",
+                        "invalid flow state, no valid flow state found",
+                    )
+                })?;
+
+            let verifier = PkceCodeVerifier::new(body.code_verifier.unwrap_or_default());
+            let challenge = PkceCodeChallenge::from_code_verifier_sha256(&verifier);
+            if challenge.as_str() != flow.code_challenge {
+                return Err(api_error(
+                    StatusCode::FORBIDDEN,
+                    "bad_code_verifier",
+                    "code challenge does not match previously saved code verifier",
+                ));
+            }
+
+            state.sign_in(&mut store, flow.user_id)?
+        }
+        grant_type => {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                &format!("unsupported_grant_type: {grant_type}"),
+            ))
+        }
+    };
+
+    Ok(Json(session).into_response())
+}
+
+async fn get_user(State(state): State<Arc<FakeState>>, headers: HeaderMap) -> HandlerResult {
+    let store = state.store();
+    let (user_id, _) = state.authenticate(&store, &headers)?;
+    let user = store.user(user_id).ok_or_else(user_not_found)?.user.clone();
+
+    Ok(Json(user).into_response())
+}
+
+#[derive(Deserialize)]
+struct UpdateUserBody {
+    email: Option<String>,
+    phone: Option<String>,
+    password: Option<String>,
+    data: Option<Value>,
+}
+
+async fn update_user(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Json(body): Json<UpdateUserBody>,
+) -> HandlerResult {
+    let mut store = state.store();
+    let (user_id, _) = state.authenticate(&store, &headers)?;
+
+    if let Some(password) = &body.password {
+        check_password(password)?;
+    }
+    if store
+        .find(body.email.as_deref(), body.phone.as_deref())
+        .is_some_and(|id| id != user_id)
+    {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "email_exists",
+            "A user with this email address has already been registered",
+        ));
+    }
+
+    let stored = store.user_mut(user_id).ok_or_else(user_not_found)?;
+    if body.password.is_some() && body.password == stored.password {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "same_password",
+            "New password should be different from the old password.",
+        ));
+    }
+    if let Some(password) = body.password {
+        stored.password = Some(password);
+    }
+    if let Some(data) = body.data {
+        merge(&mut stored.user.user_metadata, data);
+    }
+
+    let mut pending = None;
+    if let Some(email) = body.email.map(|email| email.to_lowercase()) {
+        if state.autoconfirm || stored.user.is_anonymous {
+            stored.user.email = email;
+            if state.autoconfirm {
+                confirm_email(&mut stored.user);
+            }
+        } else {
+            stored.user.new_email = Some(email);
+            pending = Some("email_change");
+        }
+    }
+    if let Some(phone) = body.phone {
+        if state.autoconfirm || stored.user.is_anonymous {
+            stored.user.phone = phone;
+            if state.autoconfirm {
+                confirm_phone(&mut stored.user);
+            }
+        } else {
+            stored.user.new_phone = Some(phone);
+            pending = Some("phone_change");
+        }
+    }
+    if stored.user.is_anonymous && (!stored.user.email.is_empty() || !stored.user.phone.is_empty())
+    {
+        stored.user.is_anonymous = false;
+    }
+    stored.user.updated_at = OffsetDateTime::now_utc();
+
+    if let Some(kind) = pending {
+        store.send_otp(user_id, kind);
+    }
+    let user = store.user(user_id).ok_or_else(user_not_found)?.user.clone();
+
+    Ok(Json(user).into_response())
+}
+
+#[derive(Deserialize)]
+struct LogoutQuery {
+    scope: Option<String>,
+}
+
+async fn logout(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Query(query): Query<LogoutQuery>,
+) -> HandlerResult {
+    let mut store = state.store();
+    let (user_id, session_id) = state.authenticate(&store, &headers)?;
+
+    match query.scope.as_deref().unwrap_or("global") {
+        "global" => store.revoke_sessions(user_id, None),
+        "local" => {
+            store.sessions.remove(&session_id);
+            store.revoke_sessions(user_id, Some(Uuid::nil()));
+            store.sessions.retain(|_, owner| *owner != Uuid::nil());
+        }
+        "others" => store.revoke_sessions(user_id, Some(session_id)),
+        scope => {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "validation_failed",
+                &format!("Unsupported logout scope {scope}"),
+            ))
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[derive(Deserialize)]
+struct OtpBody {
+    email: Option<String>,
+    phone: Option<String>,
+    create_user: Option<bool>,
+    data: Option<Value>,
+}
+
+async fn otp(State(state): State<Arc<FakeState>>, Json(body): Json<OtpBody>) -> HandlerResult {
+    let mut store = state.store();
+    let email = body.email.map(|email| email.to_lowercase());
+
+    let user_id = match store.find(email.as_deref(), body.phone.as_deref()) {
+        Some(user_id) => user_id,
+        None if !body.create_user.unwrap_or(true) => {
+            return Err(api_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "otp_disabled",
+                "Signups not allowed for otp",
+            ))
+        }
+        None if state.signup_disabled => {
+            return Err(api_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "signup_disabled",
+                "Signups not allowed for this instance",
+            ))
+        }
+        None => {
+            let provider = if email.is_some() { "email" } else { "phone" };
+            let data = body.data.unwrap_or_else(|| json!({}));
+            let user = state.new_user(email.clone(), body.phone, provider, data);
+            store.insert(user, None)
+        }
+    };
+
+    store.send_otp(user_id, if email.is_some() { "magiclink" } else { "sms" });
+
+    Ok(Json(json!({})).into_response())
+}
+
+#[derive(Deserialize)]
+struct VerifyBody {
+    #[serde(rename = "type")]
+    kind: String,
+    email: Option<String>,
+    phone: Option<String>,
+    token: Option<String>,
+    token_hash: Option<String>,
+}
+
+async fn verify(
+    State(state): State<Arc<FakeState>>,
+    Json(body): Json<VerifyBody>,
+) -> HandlerResult {
+    let mut store = state.store();
+    let invalid = || {
+        api_error(
+            StatusCode::FORBIDDEN,
+            "otp_expired",
+            "Token has expired or is invalid",
+        )
+    };
+
+    let user_id = match &body.token_hash {
+        Some(token_hash) => store
+            .otps
+            .iter()
+            .find(|(_, otp)| &otp.token_hash == token_hash)
+            .map(|(user_id, _)| *user_id),
+        None => store
+            .users
+            .iter()
+            .find(|stored| {
+                let user = &stored.user;
+                body.email.as_deref().is_some_and(|email| {
+                    user.email.eq_ignore_ascii_case(email)
+                        || user
+                            .new_email
+                            .as_deref()
+                            .is_some_and(|new| new.eq_ignore_ascii_case(email))
+                }) || body.phone.as_deref().is_some_and(|phone| {
+                    user.phone == phone || user.new_phone.as_deref() == Some(phone)
+                })
+            })
+            .map(|stored| stored.user.id),
+    }
+    .ok_or_else(invalid)?;
+
+    let otp = store.otps.get(&user_id).ok_or_else(invalid)?;
+    let matches = body.token_hash.is_some() || body.token.as_deref() == Some(otp.token.as_str());
+    let kind_matches = otp.kind == body.kind
+        || (otp.kind == "magiclink" && body.kind == "email")
+        || (otp.kind == "signup" && body.kind == "email");
+    if !matches || !kind_matches {
+        return Err(invalid());
+    }
+    store.otps.remove(&user_id);
+
+    let stored = store.user_mut(user_id).ok_or_else(user_not_found)?;
+    match body.kind.as_str() {
+        "sms" => confirm_phone(&mut stored.user),
+        "email_change" => {
+            if let Some(email) = stored.user.new_email.take() {
+                stored.user.email = email;
+            }
+            confirm_email(&mut stored.user);
+        }
+        "phone_change" => {
+            if let Some(phone) = stored.user.new_phone.take() {
+                stored.user.phone = phone;
+            }
+            confirm_phone(&mut stored.user);
+        }
+        _ => confirm_email(&mut stored.user),
+    }
+
+    Ok(Json(state.sign_in(&mut store, user_id)?).into_response())
+}
+
+#[derive(Deserialize)]
+struct ResendBody {
+    #[serde(rename = "type")]
+    kind: String,
+    email: Option<String>,
+    phone: Option<String>,
+}
+
+async fn resend(
+    State(state): State<Arc<FakeState>>,
+    Json(body): Json<ResendBody>,
+) -> HandlerResult {
+    let mut store = state.store();
+
+    // Unknown users are not reported, to prevent user enumeration.
+    if let Some(user_id) = store.find(body.email.as_deref(), body.phone.as_deref()) {
+        store.send_otp(user_id, &body.kind);
+    }
+
+    Ok(Json(json!({})).into_response())
+}
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    provider: String,
+    redirect_to: Option<String>,
+    code_challenge: Option<String>,
+}
+
+async fn authorize(
+    State(state): State<Arc<FakeState>>,
+    Query(query): Query<AuthorizeQuery>,
+) -> HandlerResult {
+    let code_challenge = query.code_challenge.ok_or_else(|| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "The fake only supports the PKCE flow",
+        )
+    })?;
+
+    let mut store = state.store();
+    let email = format!("{}-user@example.com", query.provider);
+    let user_id = match store.find(Some(&email), None) {
+        Some(user_id) => user_id,
+        None => {
+            let mut user = state.new_user(Some(email), None, &query.provider, json!({}));
+            confirm_email(&mut user);
+            store.insert(user, None)
+        }
+    };
+
+    let code = Uuid::new_v4().to_string();
+    store.flows.insert(
+        code.clone(),
+        Flow {
+            user_id,
+            code_challenge,
+        },
+    );
+
+    let mut redirect_to = Url::parse(query.redirect_to.as_deref().unwrap_or(&state.site_url))
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "validation_failed", &e.to_string()))?;
+    redirect_to.query_pairs_mut().append_pair("code", &code);
+
+    Ok(Redirect::to(redirect_to.as_str()).into_response())
+}
+
+#[derive(Deserialize)]
+struct ListUsersQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+    filter: Option<String>,
+}
+
+async fn admin_list_users(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Query(query): Query<ListUsersQuery>,
+) -> HandlerResult {
+    state.authorize_admin(&headers)?;
+    let store = state.store();
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(50).max(1);
+    let users: Vec<&User> = store
+        .users
+        .iter()
+        .map(|stored| &stored.user)
+        .filter(|user| {
+            query
+                .filter
+                .as_deref()
+                .is_none_or(|filter| user.email.contains(filter))
+        })
+        .collect();
+
+    let total = users.len();
+    let last_page = total.div_ceil(per_page).max(1);
+    let page_users: Vec<&User> = users
+        .into_iter()
+        .skip((page - 1) * per_page)
+        .take(per_page)
+        .collect();
+
+    let mut links = Vec::new();
+    if page < last_page {
+        links.push(format!(
+            r#"</admin/users?page={}&per_page={per_page}>; rel="next""#,
+            page + 1
+        ));
+    }
+    links.push(format!(
+        r#"</admin/users?page={last_page}&per_page={per_page}>; rel="last""#
+    ));
+
+    let body = Json(json!({ "users": page_users, "aud": state.aud }));
+    let headers = [
+        ("x-total-count", total.to_string()),
+        ("link", links.join(", ")),
+    ];
+
+    Ok((headers, body).into_response())
+}
+
+#[derive(Deserialize)]
+struct AdminUserBody {
+    email: Option<String>,
+    phone: Option<String>,
+    password: Option<String>,
+    email_confirm: Option<bool>,
+    phone_confirm: Option<bool>,
+    role: Option<String>,
+    app_metadata: Option<Value>,
+    user_metadata: Option<Value>,
+    ban_duration: Option<String>,
+}
+
+impl AdminUserBody {
+    fn apply(self, stored: &mut StoredUser) -> Result<(), FakeError> {
+        let user = &mut stored.user;
+
+        if let Some(password) = self.password {
+            check_password(&password)?;
+            stored.password = Some(password);
+        }
+        if let Some(email) = self.email {
+            user.email = email.to_lowercase();
+        }
+        if let Some(phone) = self.phone {
+            user.phone = phone;
+        }
+        if self.email_confirm == Some(true) {
+            confirm_email(user);
+        }
+        if self.phone_confirm == Some(true) {
+            confirm_phone(user);
+        }
+        if let Some(role) = self.role {
+            user.role = role;
+        }
+        if let Some(app_metadata) = self.app_metadata {
+            merge(&mut user.app_metadata.additional, app_metadata);
+        }
+        if let Some(user_metadata) = self.user_metadata {
+            merge(&mut user.user_metadata, user_metadata);
+        }
+        if let Some(ban_duration) = self.ban_duration {
+            user.banned_until = match ban_duration.as_str() {
+                "none" => None,
+                duration => {
+                    let duration = parse_duration(duration).ok_or_else(|| {
+                        api_error(
+                            StatusCode::BAD_REQUEST,
+                            "validation_failed",
+                            "invalid format for ban duration",
+                        )
+                    })?;
+                    Some(OffsetDateTime::now_utc() + duration)
+                }
+            };
+        }
+        user.updated_at = OffsetDateTime::now_utc();
+
+        Ok(())
+    }
+}
+
+async fn admin_create_user(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Json(body): Json<AdminUserBody>,
+) -> HandlerResult {
+    state.authorize_admin(&headers)?;
+    let mut store = state.store();
+
+    if store
+        .find(body.email.as_deref(), body.phone.as_deref())
+        .is_some()
+    {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "email_exists",
+            "A user with this email address has already been registered",
+        ));
+    }
+
+    let provider = if body.phone.is_some() && body.email.is_none() {
+        "phone"
+    } else {
+        "email"
+    };
+    let mut stored = StoredUser {
+        user: state.new_user(None, None, provider, json!({})),
+        password: None,
+    };
+    body.apply(&mut stored)?;
+    let user = stored.user.clone();
+    store.users.push(stored);
+
+    Ok(Json(user).into_response())
+}
+
+async fn admin_get_user(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+) -> HandlerResult {
+    state.authorize_admin(&headers)?;
+    let store = state.store();
+    let user = store.user(user_id).ok_or_else(user_not_found)?.user.clone();
+
+    Ok(Json(user).into_response())
+}
+
+async fn admin_update_user(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(body): Json<AdminUserBody>,
+) -> HandlerResult {
+    state.authorize_admin(&headers)?;
+    let mut store = state.store();
+
+    let stored = store.user_mut(user_id).ok_or_else(user_not_found)?;
+    body.apply(stored)?;
+    let user = stored.user.clone();
+
+    Ok(Json(user).into_response())
+}
+
+#[derive(Deserialize)]
+struct DeleteUserBody {
+    #[serde(default)]
+    should_soft_delete: bool,
+}
+
+async fn admin_delete_user(
+    State(state): State<Arc<FakeState>>,
+    headers: HeaderMap,
+    Path(user_id): Path<Uuid>,
+    Json(body): Json<DeleteUserBody>,
+) -> HandlerResult {
+    state.authorize_admin(&headers)?;
+    let mut store = state.store();
+
+    if store.user(user_id).is_none() {
+        return Err(user_not_found());
+    }
+
+    if body.should_soft_delete {
+        let stored = store.user_mut(user_id).ok_or_else(user_not_found)?;
+        stored.user.email.clear();
+        stored.user.phone.clear();
+        stored.user.deleted_at = Some(OffsetDateTime::now_utc());
+        stored.password = None;
+        store.revoke_sessions(user_id, None);
+    } else {
+        store.remove(user_id);
+    }
+
+    Ok(Json(json!({})).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_go_durations() {
+        assert_eq!(parse_duration("24h"), Some(time::Duration::hours(24)));
+        assert_eq!(parse_duration("1h30m"), Some(time::Duration::minutes(90)));
+        assert_eq!(parse_duration("90s"), Some(time::Duration::seconds(90)));
+        assert_eq!(parse_duration("forever"), None);
+    }
+}
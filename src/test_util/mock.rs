@@ -0,0 +1,410 @@
+use crate::api::{IdTokenCredentials, ResendRequest, Settings, SignUpOptions, SignUpResponse};
+use crate::auth::ClientError;
+use crate::{
+    AccessToken, Auth, AuthTypes, DefaultAuthTypes, EmailOrPhone, LogoutScope, OAuthRequest,
+    OAuthResponse, RefreshToken, Session, SessionAuth, SsoRequest, User, UserAttributes,
+};
+use serde_json::Value;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// A call recorded by [`MockAuth`]. Metadata and user attributes are recorded as JSON.
+#[derive(Debug, Clone)]
+pub enum MockCall {
+    SignUp {
+        email_or_phone: EmailOrPhone,
+        password: String,
+        data: Option<Value>,
+    },
+    SignIn {
+        email_or_phone: EmailOrPhone,
+        password: String,
+        captcha_token: Option<String>,
+    },
+    SignInWithIdToken {
+        credentials: IdTokenCredentials,
+    },
+    SignInAnonymously {
+        data: Option<Value>,
+        captcha_token: Option<String>,
+    },
+    Resend {
+        request: ResendRequest,
+    },
+    Settings,
+    ExchangeCodeForSession {
+        code: String,
+        csrf_token: String,
+    },
+    CreateOAuthUrl {
+        request: OAuthRequest,
+    },
+    SignInWithSso {
+        request: SsoRequest,
+    },
+    Logout {
+        access_token: String,
+        scope: LogoutScope,
+    },
+    Refresh {
+        access_token: String,
+        refresh_token: Option<String>,
+    },
+    UpdateUser {
+        access_token: String,
+        attributes: Value,
+    },
+    Reauthenticate {
+        access_token: String,
+    },
+    LinkIdentity {
+        access_token: String,
+        request: OAuthRequest,
+    },
+    UnlinkIdentity {
+        access_token: String,
+        identity_id: Uuid,
+    },
+}
+
+/// [`Auth`] implementation with programmable responses, to test handlers without GoTrue.
+///
+/// Responses are queued per method and returned in order, calling a method without a queued
+/// response panics. All calls, including those of the [`SessionAuth`] returned by
+/// [`Auth::with_token`], are recorded and can be inspected with [`MockAuth::calls`].
+///
+/// ```
+/// # use axum_supabase_auth::test_util::MockAuth;
+/// # use axum_supabase_auth::ClientError;
+/// let auth = MockAuth::new();
+/// auth.sign_in_returns(Err(ClientError::WrongCredentials));
+/// ```
+pub struct MockAuth<T: AuthTypes = DefaultAuthTypes> {
+    state: Arc<Mutex<MockState>>,
+    _types: PhantomData<fn() -> T>,
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<MockCall>,
+    responses: HashMap<&'static str, VecDeque<Box<dyn Any + Send>>>,
+}
+
+impl MockAuth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: AuthTypes> Default for MockAuth<T> {
+    fn default() -> Self {
+        Self {
+            state: Arc::default(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<T: AuthTypes> Clone for MockAuth<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<T: AuthTypes> MockAuth<T> {
+    /// Returns all calls so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn sign_up_returns(&self, response: Result<SignUpResponse<T>, ClientError>) -> &Self {
+        self.push("sign_up", response)
+    }
+
+    pub fn sign_in_returns(&self, response: Result<Session<T>, ClientError>) -> &Self {
+        self.push("sign_in", response)
+    }
+
+    pub fn sign_in_with_id_token_returns(
+        &self,
+        response: Result<Session<T>, ClientError>,
+    ) -> &Self {
+        self.push("sign_in_with_id_token", response)
+    }
+
+    pub fn sign_in_anonymously_returns(&self, response: Result<Session<T>, ClientError>) -> &Self {
+        self.push("sign_in_anonymously", response)
+    }
+
+    pub fn resend_returns(&self, response: Result<(), ClientError>) -> &Self {
+        self.push("resend", response)
+    }
+
+    pub fn settings_returns(&self, response: Result<Settings, ClientError>) -> &Self {
+        self.push("settings", response)
+    }
+
+    pub fn exchange_code_for_session_returns(
+        &self,
+        response: Result<Session<T>, ClientError>,
+    ) -> &Self {
+        self.push("exchange_code_for_session", response)
+    }
+
+    pub fn create_oauth_url_returns(&self, response: Result<OAuthResponse, ClientError>) -> &Self {
+        self.push("create_oauth_url", response)
+    }
+
+    pub fn sign_in_with_sso_returns(&self, response: Result<OAuthResponse, ClientError>) -> &Self {
+        self.push("sign_in_with_sso", response)
+    }
+
+    pub fn logout_returns(&self, response: Result<(), ClientError>) -> &Self {
+        self.push("logout", response)
+    }
+
+    pub fn refresh_returns(&self, response: Result<Session<T>, ClientError>) -> &Self {
+        self.push("refresh", response)
+    }
+
+    pub fn update_user_returns(&self, response: Result<User<T>, ClientError>) -> &Self {
+        self.push("update_user", response)
+    }
+
+    pub fn reauthenticate_returns(&self, response: Result<(), ClientError>) -> &Self {
+        self.push("reauthenticate", response)
+    }
+
+    pub fn link_identity_returns(&self, response: Result<OAuthResponse, ClientError>) -> &Self {
+        self.push("link_identity", response)
+    }
+
+    pub fn unlink_identity_returns(&self, response: Result<(), ClientError>) -> &Self {
+        self.push("unlink_identity", response)
+    }
+
+    fn push<R: Send + 'static>(
+        &self,
+        method: &'static str,
+        response: Result<R, ClientError>,
+    ) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .entry(method)
+            .or_default()
+            .push_back(Box::new(response));
+        self
+    }
+
+    /// Records the call and returns the next queued response of `method`.
+    fn respond<R: 'static>(&self, method: &'static str, call: MockCall) -> Result<R, ClientError> {
+        let response = {
+            let mut state = self.state.lock().unwrap();
+            state.calls.push(call);
+            state
+                .responses
+                .get_mut(method)
+                .and_then(VecDeque::pop_front)
+        };
+
+        match response {
+            Some(response) => *response
+                .downcast::<Result<R, ClientError>>()
+                .expect("MockAuth response has the wrong type"),
+            None => panic!("MockAuth: no response queued for `{method}`"),
+        }
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).expect("could not serialize to JSON")
+}
+
+impl<T: AuthTypes> Auth<T> for MockAuth<T> {
+    async fn sign_up(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: impl AsRef<str> + Send,
+        options: SignUpOptions<T>,
+    ) -> Result<SignUpResponse<T>, ClientError> {
+        let call = MockCall::SignUp {
+            email_or_phone,
+            password: password.as_ref().to_string(),
+            data: options.data.as_ref().map(to_json),
+        };
+
+        self.respond("sign_up", call)
+    }
+
+    async fn sign_in(
+        &self,
+        email_or_phone: EmailOrPhone,
+        password: impl AsRef<str> + Send,
+        captcha_token: Option<String>,
+    ) -> Result<Session<T>, ClientError> {
+        let call = MockCall::SignIn {
+            email_or_phone,
+            password: password.as_ref().to_string(),
+            captcha_token,
+        };
+
+        self.respond("sign_in", call)
+    }
+
+    async fn sign_in_with_id_token(
+        &self,
+        credentials: IdTokenCredentials,
+    ) -> Result<Session<T>, ClientError> {
+        self.respond(
+            "sign_in_with_id_token",
+            MockCall::SignInWithIdToken { credentials },
+        )
+    }
+
+    async fn sign_in_anonymously(
+        &self,
+        data: Option<T::UserData>,
+        captcha_token: Option<String>,
+    ) -> Result<Session<T>, ClientError> {
+        let call = MockCall::SignInAnonymously {
+            data: data.as_ref().map(to_json),
+            captcha_token,
+        };
+
+        self.respond("sign_in_anonymously", call)
+    }
+
+    async fn resend(&self, request: ResendRequest) -> Result<(), ClientError> {
+        self.respond("resend", MockCall::Resend { request })
+    }
+
+    async fn settings(&self) -> Result<Settings, ClientError> {
+        self.respond("settings", MockCall::Settings)
+    }
+
+    async fn exchange_code_for_session(
+        &self,
+        code: &str,
+        csrf_token_b64: &str,
+    ) -> Result<Session<T>, ClientError> {
+        let call = MockCall::ExchangeCodeForSession {
+            code: code.to_string(),
+            csrf_token: csrf_token_b64.to_string(),
+        };
+
+        self.respond("exchange_code_for_session", call)
+    }
+
+    fn create_oauth_url(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError> {
+        self.respond(
+            "create_oauth_url",
+            MockCall::CreateOAuthUrl { request: req },
+        )
+    }
+
+    async fn sign_in_with_sso(&self, req: SsoRequest) -> Result<OAuthResponse, ClientError> {
+        self.respond("sign_in_with_sso", MockCall::SignInWithSso { request: req })
+    }
+
+    fn with_token(&self, access_token: AccessToken) -> impl SessionAuth<T> + Send + Sync + 'static {
+        MockSessionAuth {
+            auth: self.clone(),
+            access_token,
+            refresh_token: None,
+        }
+    }
+
+    fn with_refresh_token(
+        &self,
+        access_token: AccessToken,
+        refresh_token: RefreshToken,
+    ) -> impl SessionAuth<T> + Send + Sync + 'static {
+        MockSessionAuth {
+            auth: self.clone(),
+            access_token,
+            refresh_token: Some(refresh_token),
+        }
+    }
+}
+
+struct MockSessionAuth<T: AuthTypes> {
+    auth: MockAuth<T>,
+    access_token: AccessToken,
+    refresh_token: Option<RefreshToken>,
+}
+
+impl<T: AuthTypes> MockSessionAuth<T> {
+    fn access_token(&self) -> String {
+        self.access_token.as_ref().to_string()
+    }
+}
+
+impl<T: AuthTypes> SessionAuth<T> for MockSessionAuth<T> {
+    async fn logout(&self, scope: LogoutScope) -> Result<(), ClientError> {
+        let call = MockCall::Logout {
+            access_token: self.access_token(),
+            scope,
+        };
+
+        self.auth.respond("logout", call)
+    }
+
+    async fn refresh(&mut self) -> Result<Session<T>, ClientError> {
+        let call = MockCall::Refresh {
+            access_token: self.access_token(),
+            refresh_token: self
+                .refresh_token
+                .as_ref()
+                .map(|token| token.as_ref().to_string()),
+        };
+
+        let session: Session<T> = self.auth.respond("refresh", call)?;
+        self.access_token = session.access_token.clone();
+        self.refresh_token = Some(session.refresh_token.clone());
+
+        Ok(session)
+    }
+
+    async fn update_user(&self, attributes: UserAttributes<T>) -> Result<User<T>, ClientError> {
+        let call = MockCall::UpdateUser {
+            access_token: self.access_token(),
+            attributes: to_json(&attributes),
+        };
+
+        self.auth.respond("update_user", call)
+    }
+
+    async fn reauthenticate(&self) -> Result<(), ClientError> {
+        let call = MockCall::Reauthenticate {
+            access_token: self.access_token(),
+        };
+
+        self.auth.respond("reauthenticate", call)
+    }
+
+    async fn link_identity(&self, req: OAuthRequest) -> Result<OAuthResponse, ClientError> {
+        let call = MockCall::LinkIdentity {
+            access_token: self.access_token(),
+            request: req,
+        };
+
+        self.auth.respond("link_identity", call)
+    }
+
+    async fn unlink_identity(&self, identity_id: Uuid) -> Result<(), ClientError> {
+        let call = MockCall::UnlinkIdentity {
+            access_token: self.access_token(),
+            identity_id,
+        };
+
+        self.auth.respond("unlink_identity", call)
+    }
+}
@@ -0,0 +1,11 @@
+//! Utilities to test applications and this crate without a running GoTrue instance.
+//!
+//! Requires the `test-util` feature.
+
+mod gotrue;
+mod mock;
+mod token;
+
+pub use gotrue::FakeGoTrue;
+pub use mock::{MockAuth, MockCall};
+pub use token::TestToken;
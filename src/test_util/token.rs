@@ -0,0 +1,137 @@
+use crate::{AuthTypes, DefaultAuthTypes};
+use axum::http::HeaderValue;
+use axum_extra::extract::cookie::Cookie;
+use bon::Builder;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Signed access token with the claims GoTrue issues, decodable into
+/// [`Claims`](crate::middleware::Claims) of `T`.
+///
+/// ```
+/// # use axum_supabase_auth::test_util::TestToken;
+/// # use axum::http::{header, Request};
+/// # use jsonwebtoken::EncodingKey;
+/// let token: TestToken = TestToken::builder()
+///     .key(EncodingKey::from_secret(b"secret"))
+///     .email("alice@example.com")
+///     .build();
+///
+/// let request = Request::get("/profile")
+///     .header(header::COOKIE, token.cookie_header("sb-auth"))
+///     .body(())
+///     .unwrap();
+/// ```
+#[derive(Builder)]
+pub struct TestToken<T: AuthTypes = DefaultAuthTypes> {
+    /// Key the token is signed with, usually [`EncodingKey::from_secret`] with the JWT secret.
+    key: EncodingKey,
+    #[builder(default)]
+    algorithm: Algorithm,
+
+    #[builder(default = Uuid::new_v4())]
+    sub: Uuid,
+    #[builder(default = Uuid::new_v4())]
+    session_id: Uuid,
+    #[builder(into)]
+    email: Option<String>,
+    #[builder(into)]
+    phone: Option<String>,
+    #[builder(into, default = "authenticated")]
+    role: String,
+    #[builder(into, default = "authenticated")]
+    aud: String,
+    /// Authenticator assurance level, `aal1` or `aal2` after MFA.
+    #[builder(into, default = "aal1")]
+    aal: String,
+    #[builder(default = OffsetDateTime::now_utc() + time::Duration::hours(1))]
+    expires_at: OffsetDateTime,
+    #[builder(default)]
+    is_anonymous: bool,
+    #[builder(into, default = "email")]
+    provider: String,
+
+    app_metadata: Option<T::AppData>,
+    user_metadata: Option<T::UserData>,
+    additional: Option<T::AdditionalData>,
+}
+
+impl<T: AuthTypes> TestToken<T> {
+    pub fn sub(&self) -> Uuid {
+        self.sub
+    }
+
+    /// Returns the signed JWT.
+    pub fn access_token(&self) -> String {
+        jsonwebtoken::encode(&Header::new(self.algorithm), &self.claims(), &self.key)
+            .expect("could not encode test token")
+    }
+
+    /// Returns the auth cookie as set by the login handlers.
+    pub fn cookie(&self, name: impl Into<String>) -> Cookie<'static> {
+        Cookie::build((name.into(), self.access_token()))
+            .path("/")
+            .build()
+    }
+
+    /// Returns a `Cookie` header value carrying the auth cookie, for requests against routers
+    /// using the extractors.
+    pub fn cookie_header(&self, name: &str) -> HeaderValue {
+        let cookie = Cookie::new(name, self.access_token());
+
+        HeaderValue::from_str(&cookie.encoded().to_string()).expect("invalid cookie header")
+    }
+
+    /// Returns a `Bearer` authorization header value, accepted by the extractors when there is
+    /// no auth cookie.
+    pub fn authorization_header(&self) -> HeaderValue {
+        HeaderValue::from_str(&format!("Bearer {}", self.access_token()))
+            .expect("invalid authorization header")
+    }
+
+    fn claims(&self) -> Value {
+        let mut app_metadata = object(json!({
+            "provider": self.provider,
+            "providers": [self.provider],
+        }));
+        app_metadata.extend(self.app_metadata.as_ref().map(object).unwrap_or_default());
+
+        let user_metadata = self
+            .user_metadata
+            .as_ref()
+            .map_or_else(|| json!({}), to_value);
+
+        let mut claims = object(json!({
+            "aud": self.aud,
+            "exp": self.expires_at.unix_timestamp(),
+            "iat": OffsetDateTime::now_utc().unix_timestamp(),
+            "sub": self.sub,
+            "email": self.email.as_deref().unwrap_or_default(),
+            "phone": self.phone.as_deref().unwrap_or_default(),
+            "role": self.role,
+            "aal": self.aal,
+            "session_id": self.session_id,
+            "is_anonymous": self.is_anonymous,
+        }));
+        claims.extend(self.additional.as_ref().map(object).unwrap_or_default());
+        claims.insert("app_metadata".to_string(), Value::Object(app_metadata));
+        claims.insert("user_metadata".to_string(), user_metadata);
+
+        Value::Object(claims)
+    }
+}
+
+fn to_value(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).expect("could not serialize claims")
+}
+
+/// Serializes `value` as JSON object, anything else is ignored.
+fn object(value: impl Serialize) -> Map<String, Value> {
+    match to_value(&value) {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
@@ -1,4 +1,4 @@
-use axum_supabase_auth::api::{Api, ApiClient};
+use axum_supabase_auth::api::{Api, ApiClient, SignUpOptions};
 use axum_supabase_auth::{EmailOrPhone, Session};
 use fake::faker::internet::en::{FreeEmail, Password};
 use fake::Fake;
@@ -6,7 +6,6 @@ use jsonwebtoken::{encode, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-const PROJECT_REFERENCE: &str = "project_ref";
 const API_KEY: &str = "api_key";
 const JWT_SECRET: &str = "secret";
 
@@ -51,7 +50,11 @@ pub async fn sign_up(client: &impl Api) -> (Session, Credentials) {
     let password = generate_password();
 
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), &password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            &password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("Failed to sign up");
 
@@ -0,0 +1,73 @@
+use crate::helpers::{admin_token, generate_email, generate_password, spawn_test};
+use axum_supabase_auth::api::{Api, GenerateLinkRequest, GenerateLinkType, InviteUserRequest};
+use test_log::test;
+
+#[test(tokio::test)]
+async fn invite_user() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let request = InviteUserRequest::builder().email(email.clone()).build();
+
+    // Act
+    let user = client
+        .invite_user(admin_token(), &request)
+        .await
+        .expect("could not invite user");
+
+    // Assert
+    assert_eq!(user.email, email);
+}
+
+#[test(tokio::test)]
+async fn generate_signup_link() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let request = GenerateLinkRequest::builder()
+        .link_type(GenerateLinkType::Signup)
+        .email(email.clone())
+        .password(generate_password())
+        .build();
+
+    // Act
+    let result = client
+        .generate_link(admin_token(), &request)
+        .await
+        .expect("could not generate link");
+
+    // Assert
+    assert_eq!(result.user.email, email);
+    assert_eq!(result.verification_type, "signup");
+    assert!(!result.action_link.is_empty());
+    assert!(!result.hashed_token.is_empty());
+}
+
+#[test(tokio::test)]
+async fn generate_magiclink() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let invite = InviteUserRequest::builder().email(email.clone()).build();
+    client
+        .invite_user(admin_token(), &invite)
+        .await
+        .expect("could not invite user");
+    let request = GenerateLinkRequest::builder()
+        .link_type(GenerateLinkType::Magiclink)
+        .email(email.clone())
+        .build();
+
+    // Act
+    let result = client
+        .generate_link(admin_token(), &request)
+        .await
+        .expect("could not generate link");
+
+    // Assert
+    assert_eq!(result.user.email, email);
+    assert!(!result.email_otp.is_empty());
+}
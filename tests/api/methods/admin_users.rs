@@ -0,0 +1,244 @@
+use crate::helpers::{admin_token, generate_email, generate_password, spawn_test};
+use axum::http::StatusCode;
+use axum_supabase_auth::api::{AdminUserAttributes, Api, ApiClient, ApiError, ListUsersQuery};
+use axum_supabase_auth::middleware::Empty;
+use axum_supabase_auth::{AdminAuth, AdminAuthService, AuthService, AuthTypes};
+use futures_util::TryStreamExt;
+use matches::assert_matches;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use uuid::Uuid;
+
+#[test(tokio::test)]
+async fn admin_create_user() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let attributes = AdminUserAttributes::builder()
+        .email(email.clone())
+        .password(generate_password())
+        .email_confirm(true)
+        .user_metadata(json!({ "name": "Jane" }))
+        .build();
+
+    // Act
+    let user = client
+        .admin_create_user(admin_token(), &attributes)
+        .await
+        .expect("could not create user");
+
+    // Assert
+    assert_eq!(user.email, email);
+    assert!(user.email_confirmed_at.is_some());
+}
+
+#[test(tokio::test)]
+async fn admin_get_user() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let attributes = AdminUserAttributes::builder().email(email.clone()).build();
+    let created = client
+        .admin_create_user(admin_token(), &attributes)
+        .await
+        .expect("could not create user");
+    let user_id = created.id;
+
+    // Act
+    let user = client
+        .admin_get_user(admin_token(), user_id)
+        .await
+        .expect("could not get user");
+
+    // Assert
+    assert_eq!(user.id, created.id);
+    assert_eq!(user.email, email);
+}
+
+#[test(tokio::test)]
+async fn admin_get_user_not_found() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+
+    // Act
+    let result = client.admin_get_user(admin_token(), Uuid::new_v4()).await;
+
+    // Assert
+    assert_matches!(result, Err(ApiError::Request(StatusCode::NOT_FOUND, _, _)));
+}
+
+#[test(tokio::test)]
+async fn admin_update_user() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let attributes = AdminUserAttributes::builder()
+        .email(generate_email())
+        .build();
+    let created = client
+        .admin_create_user(admin_token(), &attributes)
+        .await
+        .expect("could not create user");
+    let user_id = created.id;
+    let new_email = generate_email();
+
+    // Act
+    let user = client
+        .admin_update_user(
+            admin_token(),
+            user_id,
+            &AdminUserAttributes::builder()
+                .email(new_email.clone())
+                .email_confirm(true)
+                .build(),
+        )
+        .await
+        .expect("could not update user");
+
+    // Assert
+    assert_eq!(user.email, new_email);
+}
+
+#[test(tokio::test)]
+async fn admin_delete_user() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let attributes = AdminUserAttributes::builder()
+        .email(generate_email())
+        .build();
+    let created = client
+        .admin_create_user(admin_token(), &attributes)
+        .await
+        .expect("could not create user");
+    let user_id = created.id;
+
+    // Act
+    client
+        .admin_delete_user(admin_token(), user_id, false)
+        .await
+        .expect("could not delete user");
+
+    // Assert
+    let result = client.admin_get_user(admin_token(), user_id).await;
+    assert_matches!(result, Err(ApiError::Request(StatusCode::NOT_FOUND, _, _)));
+}
+
+#[test(tokio::test)]
+async fn admin_list_users_paginated() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    for _ in 0..2 {
+        let attributes = AdminUserAttributes::builder()
+            .email(generate_email())
+            .build();
+        client
+            .admin_create_user(admin_token(), &attributes)
+            .await
+            .expect("could not create user");
+    }
+
+    // Act
+    let result = client
+        .list_users_query(
+            admin_token(),
+            &ListUsersQuery::builder().page(1).per_page(1).build(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(result.users.len(), 1);
+    assert!(result.total.unwrap() >= 2);
+    assert_eq!(result.next_page, Some(2));
+}
+
+#[test(tokio::test)]
+async fn admin_stream_users() {
+    // Arrange
+    let admin: AdminAuthService =
+        AuthService::new("http://localhost:9998".try_into().unwrap(), "api_key")
+            .with_service_role_key(admin_token());
+    let mut emails = Vec::new();
+    for _ in 0..3 {
+        let attributes = AdminUserAttributes::builder()
+            .email(generate_email())
+            .build();
+        let user = admin
+            .create_user(attributes)
+            .await
+            .expect("could not create user");
+        emails.push(user.email);
+    }
+
+    // Act
+    let users: Vec<_> = admin
+        .stream_users(ListUsersQuery::builder().per_page(2).build())
+        .try_collect()
+        .await
+        .expect("could not stream users");
+
+    // Assert
+    for email in emails {
+        assert!(users.iter().any(|u| u.email == email));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Profile {
+    display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Roles {
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+struct TypedAuth;
+
+impl AuthTypes for TypedAuth {
+    type AppData = Roles;
+    type UserData = Profile;
+    type AdditionalData = Empty;
+}
+
+#[test(tokio::test)]
+async fn admin_user_typed_metadata() {
+    // Arrange
+    let client = ApiClient::<TypedAuth>::new(
+        "http://localhost:9998".try_into().unwrap(),
+        Duration::from_secs(1),
+        "api_key",
+    );
+    let profile = Profile {
+        display_name: "Jane".to_string(),
+    };
+    let attributes = AdminUserAttributes::builder()
+        .email(generate_email())
+        .app_metadata(Roles {
+            roles: vec!["editor".to_string()],
+        })
+        .user_metadata(profile.clone())
+        .build();
+    let created = client
+        .admin_create_user(admin_token(), &attributes)
+        .await
+        .expect("could not create user");
+
+    // Act
+    let user = client
+        .admin_get_user(admin_token(), created.id)
+        .await
+        .expect("could not get user");
+
+    // Assert
+    assert_eq!(user.user_metadata, profile);
+    assert_eq!(user.app_metadata.additional.roles, vec!["editor"]);
+}
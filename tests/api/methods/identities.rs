@@ -0,0 +1,56 @@
+use crate::helpers::{sign_up, spawn_test};
+use axum_supabase_auth::api::{Api, ApiError, ApiErrorCode};
+use axum_supabase_auth::OAuthRequest;
+use matches::assert_matches;
+use oauth2::PkceCodeChallenge;
+use test_log::test;
+
+// Manual linking is not enabled on the test instances, GoTrue rejects both
+// operations before looking at the identity.
+
+#[test(tokio::test)]
+async fn link_identity_manual_linking_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (session, _) = sign_up(&client).await;
+    let (challenge, _) = PkceCodeChallenge::new_random_sha256();
+
+    // Act
+    let result = client
+        .create_pkce_link_identity_url(
+            &session.access_token,
+            OAuthRequest {
+                provider: "github".to_string(),
+                redirect_to: None,
+            },
+            challenge,
+        )
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(_, ApiErrorCode::ManualLinkingDisabled, _))
+    );
+}
+
+#[test(tokio::test)]
+async fn unlink_identity_manual_linking_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (session, _) = sign_up(&client).await;
+    let identity = &session.user.identities[0];
+
+    // Act
+    let result = client
+        .unlink_identity(&session.access_token, identity.identity_id)
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(_, ApiErrorCode::ManualLinkingDisabled, _))
+    );
+}
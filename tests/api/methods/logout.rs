@@ -1,6 +1,7 @@
 use crate::helpers::{sign_up, spawn_test};
 use axum::http::StatusCode;
 use axum_supabase_auth::api::{Api, ApiError};
+use axum_supabase_auth::{EmailOrPhone, LogoutScope};
 use matches::assert_matches;
 use test_log::test;
 
@@ -13,7 +14,7 @@ async fn logout() {
 
     // Act
     client
-        .logout(&session.access_token)
+        .logout(&session.access_token, LogoutScope::Global)
         .await
         .expect("could not logout");
 
@@ -23,3 +24,65 @@ async fn logout() {
     // Assert
     assert_matches!(me, Err(ApiError::Request(StatusCode::FORBIDDEN, _, _)));
 }
+
+#[test(tokio::test)]
+async fn logout_others() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (session, credentials) = sign_up(&client).await;
+    let other_session = client
+        .sign_in(
+            EmailOrPhone::Email(credentials.email),
+            credentials.password,
+            None,
+        )
+        .await
+        .expect("sign in failed");
+
+    // Act
+    client
+        .logout(&session.access_token, LogoutScope::Others)
+        .await
+        .expect("could not logout");
+
+    // Assert
+    let other = client
+        .refresh_access_token(other_session.refresh_token)
+        .await;
+    assert_matches!(other, Err(ApiError::OAuth(_, _, _)));
+    client
+        .refresh_access_token(session.refresh_token)
+        .await
+        .expect("current session should still be valid");
+}
+
+#[test(tokio::test)]
+async fn logout_local() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (session, credentials) = sign_up(&client).await;
+    let other_session = client
+        .sign_in(
+            EmailOrPhone::Email(credentials.email),
+            credentials.password,
+            None,
+        )
+        .await
+        .expect("sign in failed");
+
+    // Act
+    client
+        .logout(&session.access_token, LogoutScope::Local)
+        .await
+        .expect("could not logout");
+
+    // Assert
+    let current = client.refresh_access_token(session.refresh_token).await;
+    assert_matches!(current, Err(ApiError::OAuth(_, _, _)));
+    client
+        .refresh_access_token(other_session.refresh_token)
+        .await
+        .expect("other session should still be valid");
+}
@@ -1,7 +1,16 @@
+mod admin_links;
+mod admin_users;
 mod get_user;
 mod health;
+mod identities;
 mod list_users;
 mod logout;
+mod reauthenticate;
 mod refresh_token;
+mod resend;
+mod settings;
 mod sign_in;
+mod sign_in_anonymously;
+mod sign_in_with_id_token;
 mod sign_up;
+mod sso;
@@ -0,0 +1,32 @@
+use crate::helpers::{sign_up, spawn_test};
+use axum::http::StatusCode;
+use axum_supabase_auth::api::{Api, ApiError};
+use matches::assert_matches;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn reauthenticate() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (session, _) = sign_up(&client).await;
+
+    // Act
+    let result = client.reauthenticate(&session.access_token).await;
+
+    // Assert
+    result.expect("reauthenticate failed");
+}
+
+#[test(tokio::test)]
+async fn reauthenticate_invalid_token() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+
+    // Act
+    let result = client.reauthenticate("invalid").await;
+
+    // Assert
+    assert_matches!(result, Err(ApiError::Request(StatusCode::FORBIDDEN, _, _)));
+}
@@ -0,0 +1,34 @@
+use crate::helpers::{generate_email, generate_password, spawn_test};
+use axum_supabase_auth::api::{Api, ResendRequest, ResendType, SignUpOptions};
+use axum_supabase_auth::EmailOrPhone;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn resend_signup() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.client;
+    let email = generate_email();
+    client
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            generate_password(),
+            &SignUpOptions::new(),
+        )
+        .await
+        .expect("sign up failed");
+
+    // Act
+    let result = client
+        .resend(
+            &ResendRequest::builder()
+                .kind(ResendType::Signup)
+                .target(EmailOrPhone::Email(email))
+                .email_redirect_to("http://localhost:3000/welcome")
+                .build(),
+        )
+        .await;
+
+    // Assert
+    result.expect("resend failed");
+}
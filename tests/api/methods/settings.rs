@@ -0,0 +1,36 @@
+use crate::helpers::spawn_test;
+use axum_supabase_auth::api::Api;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn settings() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+
+    // Act
+    let settings = client.settings().await.expect("could not fetch settings");
+
+    // Assert
+    assert!(settings.is_provider_enabled("github"));
+    assert!(settings.is_provider_enabled("email"));
+    assert!(!settings.is_provider_enabled("gitlab"));
+    assert!(settings.enabled_providers().any(|p| p == "phone"));
+    assert!(!settings.disable_signup);
+    assert!(settings.mailer_autoconfirm);
+    assert!(settings.phone_autoconfirm);
+}
+
+#[test(tokio::test)]
+async fn settings_signup_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.signup_disabled_client;
+
+    // Act
+    let settings = client.settings().await.expect("could not fetch settings");
+
+    // Assert
+    assert!(settings.disable_signup);
+    assert!(!settings.is_provider_enabled("email"));
+}
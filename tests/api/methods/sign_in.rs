@@ -20,6 +20,7 @@ async fn sign_in() {
         .sign_in(
             EmailOrPhone::Email(credentials.email.clone()),
             credentials.password,
+            None,
         )
         .await
         .expect("sign in failed");
@@ -45,6 +46,7 @@ async fn sign_in_wrong_password() {
         .sign_in(
             EmailOrPhone::Email(credentials.email.clone()),
             wrong_password,
+            None,
         )
         .await;
 
@@ -64,7 +66,9 @@ async fn sign_in_non_existent() {
     let password = generate_password();
 
     // Act
-    let result = client.sign_in(EmailOrPhone::Email(email), password).await;
+    let result = client
+        .sign_in(EmailOrPhone::Email(email), password, None)
+        .await;
 
     // Assert
     assert_matches!(
@@ -0,0 +1,72 @@
+use crate::helpers::{generate_email, generate_password, spawn_test};
+use axum_supabase_auth::api::{Api, ApiError, ApiErrorCode};
+use axum_supabase_auth::UserAttributes;
+use matches::assert_matches;
+use serde_json::json;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn sign_in_anonymously() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let data = json!({ "cart": "42" });
+
+    // Act
+    let session = client
+        .sign_in_anonymously(Some(&data), None)
+        .await
+        .expect("anonymous sign in failed");
+
+    // Assert
+    assert!(session.user.is_anonymous);
+    assert!(session.user.email.is_empty());
+    assert_eq!(session.user.user_metadata["cart"], "42");
+}
+
+#[test(tokio::test)]
+async fn sign_in_anonymously_convert_to_permanent() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let session = client
+        .sign_in_anonymously(None, None)
+        .await
+        .expect("anonymous sign in failed");
+    let email = generate_email();
+    let attributes = UserAttributes::builder()
+        .email(email.clone())
+        .password(generate_password())
+        .build();
+
+    // Act
+    let user = client
+        .update_user(&session.access_token, &attributes)
+        .await
+        .expect("could not update user");
+
+    // Assert
+    assert_eq!(user.id, session.user.id);
+    assert_eq!(user.email, email);
+    assert!(!user.is_anonymous);
+}
+
+#[test(tokio::test)]
+async fn sign_in_anonymously_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.client;
+
+    // Act
+    let result = client.sign_in_anonymously(None, None).await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            _,
+            ApiErrorCode::AnonymousProviderDisabled,
+            _
+        ))
+    );
+}
@@ -0,0 +1,26 @@
+use crate::helpers::{generate_password, spawn_test};
+use axum::http::StatusCode;
+use axum_supabase_auth::api::{Api, ApiError, IdTokenCredentials, IdTokenProvider};
+use matches::assert_matches;
+use test_log::test;
+
+#[test(tokio::test)]
+async fn sign_in_with_id_token_invalid_token() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let credentials = IdTokenCredentials::builder()
+        .provider(IdTokenProvider::Google)
+        .id_token(generate_password())
+        .build();
+
+    // Act
+    let result = client.sign_in_with_id_token(&credentials).await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(StatusCode::BAD_REQUEST, _, _))
+            | Err(ApiError::OAuth(StatusCode::BAD_REQUEST, _, _))
+    );
+}
@@ -1,6 +1,6 @@
 use crate::helpers::{generate_email, generate_password, spawn_test};
 use axum::http::StatusCode;
-use axum_supabase_auth::api::{Api, ApiError};
+use axum_supabase_auth::api::{Api, ApiError, SignUpOptions};
 use axum_supabase_auth::{EmailOrPhone, User};
 use matches::assert_matches;
 use test_log::test;
@@ -15,7 +15,11 @@ async fn sign_up() {
 
     // Act
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("Failed to sign up");
 
@@ -35,12 +39,20 @@ async fn sign_up_twice() {
 
     // Act
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("First sign up failed");
 
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), second_password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            second_password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("Second sign up failed, but should produce fake data");
 
@@ -59,7 +71,11 @@ async fn sign_up_autoconfirm() {
 
     // Act
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await
         .unwrap();
 
@@ -77,12 +93,20 @@ async fn sign_up_autoconfirm_twice() {
     let new_password = generate_password();
 
     client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("first sign up failed");
 
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), new_password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            new_password,
+            &SignUpOptions::new(),
+        )
         .await;
 
     // Second sign up produces unprocessable entity as user can not directly be logged in.
@@ -100,7 +124,11 @@ async fn sign_up_disabled() {
     let password = generate_password();
 
     let result = client
-        .sign_up(EmailOrPhone::Email(email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await;
 
     // Should result in an HTTP Error
@@ -121,11 +149,19 @@ async fn session_and_user_impl_as_ref() {
 
     // Act
     let user_response = client
-        .sign_up(EmailOrPhone::Email(email.clone()), &password)
+        .sign_up(
+            EmailOrPhone::Email(email.clone()),
+            &password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("Failed to sign up");
     let session_response = autoconfirm_client
-        .sign_up(EmailOrPhone::Email(second_email.clone()), password)
+        .sign_up(
+            EmailOrPhone::Email(second_email.clone()),
+            password,
+            &SignUpOptions::new(),
+        )
         .await
         .expect("Failed to sign up");
 
@@ -135,3 +171,27 @@ async fn session_and_user_impl_as_ref() {
     let session: &User = session_response.as_ref();
     assert_eq!(session.email, second_email);
 }
+
+#[test(tokio::test)]
+async fn sign_up_with_metadata() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let email = generate_email();
+    let password = generate_password();
+    let options: SignUpOptions = SignUpOptions::builder()
+        .data(serde_json::json!({ "display_name": "Jane", "locale": "de" }))
+        .email_redirect_to("http://localhost:3000/welcome")
+        .build();
+
+    // Act
+    let result = client
+        .sign_up(EmailOrPhone::Email(email), password, &options)
+        .await
+        .expect("Failed to sign up");
+
+    // Assert
+    let session = result.session().expect("Expected session but got user");
+    assert_eq!(session.user.user_metadata["display_name"], "Jane");
+    assert_eq!(session.user.user_metadata["locale"], "de");
+}
@@ -0,0 +1,58 @@
+use crate::helpers::{admin_token, spawn_test};
+use axum::http::StatusCode;
+use axum_supabase_auth::api::{Api, ApiError, ApiErrorCode};
+use axum_supabase_auth::{SsoRequest, SsoTarget};
+use matches::assert_matches;
+use oauth2::PkceCodeChallenge;
+use test_log::test;
+
+// SAML is not enabled on the test instances.
+
+#[test(tokio::test)]
+async fn sso_saml_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+    let (challenge, _) = PkceCodeChallenge::new_random_sha256();
+
+    // Act
+    let result = client
+        .create_pkce_sso_url(
+            SsoRequest {
+                target: SsoTarget::Domain("example.com".to_string()),
+                redirect_to: None,
+            },
+            challenge,
+        )
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            StatusCode::NOT_FOUND,
+            ApiErrorCode::SamlProviderDisabled,
+            _
+        ))
+    );
+}
+
+#[test(tokio::test)]
+async fn admin_list_sso_providers_saml_disabled() {
+    // Arrange
+    let helpers = spawn_test();
+    let client = helpers.autoconfirm_client;
+
+    // Act
+    let result = client.admin_list_sso_providers(admin_token()).await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            StatusCode::NOT_FOUND,
+            ApiErrorCode::SamlProviderDisabled,
+            _
+        ))
+    );
+}
@@ -0,0 +1,328 @@
+use axum_supabase_auth::api::{
+    AdminUserAttributes, Api, ApiClient, ApiError, ApiErrorCode, ListUsersQuery, OAuthErrorCode,
+    ResendRequest, ResendType, SignUpOptions,
+};
+use axum_supabase_auth::test_util::FakeGoTrue;
+use axum_supabase_auth::{Auth, AuthService, ClientError, EmailOrPhone, LogoutScope, OAuthRequest};
+use matches::assert_matches;
+use oauth2::PkceCodeChallenge;
+use reqwest::StatusCode;
+use test_log::test;
+
+const EMAIL: &str = "alice@example.com";
+const PASSWORD: &str = "password";
+
+async fn sign_up(client: &ApiClient) {
+    client
+        .sign_up(
+            EmailOrPhone::Email(EMAIL.to_string()),
+            PASSWORD,
+            &SignUpOptions::new(),
+        )
+        .await
+        .expect("sign up failed");
+}
+
+#[test(tokio::test)]
+async fn sign_up_and_sign_in() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+
+    // Act
+    let session = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await
+        .expect("sign in failed");
+
+    // Assert
+    let user = client
+        .get_user(&session.access_token)
+        .await
+        .expect("get user failed");
+    assert_eq!(user.email, EMAIL);
+    assert!(user.email_confirmed_at.is_some());
+}
+
+#[test(tokio::test)]
+async fn sign_up_twice_with_autoconfirm() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+
+    // Act
+    let result = client
+        .sign_up(
+            EmailOrPhone::Email(EMAIL.to_string()),
+            PASSWORD,
+            &SignUpOptions::new(),
+        )
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ApiErrorCode::UserAlreadyExists,
+            _
+        ))
+    );
+}
+
+#[test(tokio::test)]
+async fn sign_up_disabled() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().signup_disabled(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+
+    // Act
+    let result = client
+        .sign_up(
+            EmailOrPhone::Email(EMAIL.to_string()),
+            PASSWORD,
+            &SignUpOptions::new(),
+        )
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(_, ApiErrorCode::SignupDisabled, _))
+    );
+}
+
+#[test(tokio::test)]
+async fn service_sign_up_errors() {
+    // Arrange
+    let disabled = FakeGoTrue::builder().signup_disabled(true).start().await;
+    let gotrue = FakeGoTrue::builder().start().await;
+    let disabled: AuthService = disabled.auth_service();
+    let auth: AuthService = gotrue.auth_service();
+
+    // Act
+    let disabled = disabled
+        .sign_up(
+            EmailOrPhone::Email(EMAIL.to_string()),
+            PASSWORD,
+            SignUpOptions::new(),
+        )
+        .await;
+    let invalid = auth
+        .sign_up(
+            EmailOrPhone::Email("alice".to_string()),
+            PASSWORD,
+            SignUpOptions::new(),
+        )
+        .await;
+
+    // Assert
+    assert_matches!(disabled, Err(ClientError::SignUpDisabled));
+    assert_matches!(invalid, Err(ClientError::InvalidSignUp(_)));
+}
+
+#[test(tokio::test)]
+async fn sign_in_unconfirmed() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+
+    // Act
+    let result = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(StatusCode::BAD_REQUEST, _, _))
+    );
+    assert!(gotrue.otp(EMAIL).is_some());
+}
+
+#[test(tokio::test)]
+async fn resend_replaces_otp() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+    let first = gotrue.otp(EMAIL).expect("no OTP sent");
+    let request = ResendRequest::builder()
+        .kind(ResendType::Signup)
+        .target(EmailOrPhone::Email(EMAIL.to_string()))
+        .build();
+
+    // Act
+    client.resend(&request).await.expect("resend failed");
+
+    // Assert
+    let second = gotrue.otp(EMAIL).expect("no OTP sent");
+    assert_ne!(first, second);
+}
+
+#[test(tokio::test)]
+async fn refresh_token_rotates() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+    let session = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await
+        .expect("sign in failed");
+
+    // Act
+    let refreshed = client
+        .refresh_access_token(&session.refresh_token)
+        .await
+        .expect("refresh failed");
+    let reused = client.refresh_access_token(&session.refresh_token).await;
+
+    // Assert
+    assert_ne!(refreshed.refresh_token, session.refresh_token);
+    assert_matches!(
+        reused,
+        Err(ApiError::OAuth(_, OAuthErrorCode::InvalidGrant, _))
+    );
+}
+
+#[test(tokio::test)]
+async fn logout_revokes_session() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+    let session = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await
+        .expect("sign in failed");
+
+    // Act
+    client
+        .logout(&session.access_token, LogoutScope::Global)
+        .await
+        .expect("logout failed");
+
+    // Assert
+    let result = client.get_user(&session.access_token).await;
+    assert_matches!(result, Err(ApiError::Request(StatusCode::FORBIDDEN, _, _)));
+}
+
+#[test(tokio::test)]
+async fn admin_users() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().start().await;
+    let client: ApiClient = gotrue.api_client();
+    let token = gotrue.service_role_key();
+    let attributes = AdminUserAttributes::builder()
+        .email(EMAIL)
+        .password(PASSWORD)
+        .email_confirm(true)
+        .build();
+
+    // Act
+    let user = client
+        .admin_create_user(&token, &attributes)
+        .await
+        .expect("create user failed");
+    client
+        .admin_create_user(
+            &token,
+            &AdminUserAttributes::builder()
+                .email("bob@example.com")
+                .build(),
+        )
+        .await
+        .expect("create user failed");
+    let page = client
+        .list_users_query(&token, &ListUsersQuery::builder().per_page(1).build())
+        .await
+        .expect("list users failed");
+    client
+        .admin_delete_user(&token, user.id, false)
+        .await
+        .expect("delete user failed");
+
+    // Assert
+    assert_eq!(page.users.len(), 1);
+    assert_eq!(page.total, Some(2));
+    assert_eq!(page.next_page, Some(2));
+    let result = client.admin_get_user(&token, user.id).await;
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            StatusCode::NOT_FOUND,
+            ApiErrorCode::UserNotFound,
+            _
+        ))
+    );
+}
+
+#[test(tokio::test)]
+async fn admin_requires_service_role() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+    sign_up(&client).await;
+    let session = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await
+        .expect("sign in failed");
+
+    // Act
+    let result = client.list_users(&session.access_token).await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::Request(
+            StatusCode::FORBIDDEN,
+            ApiErrorCode::NotAdmin,
+            _
+        ))
+    );
+}
+
+#[test(tokio::test)]
+async fn pkce_oauth_flow() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().start().await;
+    let client: ApiClient = gotrue.api_client();
+    let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+    let url = client.create_pkce_oauth_url(
+        OAuthRequest {
+            provider: "github".to_string(),
+            redirect_to: Some("http://localhost:3000/login/confirm".to_string()),
+        },
+        challenge,
+    );
+    let http = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let response = http.get(url).send().await.expect("authorize failed");
+    let location = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .and_then(|location| url::Url::parse(location).ok())
+        .expect("missing redirect");
+    let code = location
+        .query_pairs()
+        .find(|(key, _)| key == "code")
+        .map(|(_, code)| code.into_owned())
+        .expect("missing code");
+
+    // Act
+    let session = client
+        .exchange_code_for_session(&code, &verifier)
+        .await
+        .expect("exchange failed");
+
+    // Assert
+    assert_eq!(session.user.email, "github-user@example.com");
+    assert_eq!(session.user.app_metadata.provider, "github");
+}
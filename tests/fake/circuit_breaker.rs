@@ -0,0 +1,139 @@
+use axum::body::Bytes;
+use axum::http::{Request, Response};
+use axum_supabase_auth::api::{HttpTransport, RetryPolicy, TransportError, TransportErrorKind};
+use axum_supabase_auth::{
+    Auth, AuthService, CircuitBreaker, CircuitBreakerConfig, CircuitState, ClientError,
+    DefaultAuthTypes, EmailOrPhone, SupabaseAuth, SupabaseAuthConfig,
+};
+use matches::assert_matches;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use test_log::test;
+
+/// Fails every request with a timeout and counts them.
+#[derive(Clone, Default)]
+struct TimingOutTransport {
+    requests: Arc<AtomicU32>,
+}
+
+impl HttpTransport for TimingOutTransport {
+    async fn send(&self, _request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        Err(TransportError::new(
+            TransportErrorKind::Timeout,
+            "timed out",
+        ))
+    }
+}
+
+fn service(transport: TimingOutTransport, circuit_breaker: CircuitBreaker) -> AuthService {
+    AuthService::builder()
+        .url("http://gotrue.test/".parse().unwrap())
+        .api_key("api_key")
+        .transport(transport)
+        .retry_policy(RetryPolicy::none())
+        .circuit_breaker(circuit_breaker)
+        .build()
+        .unwrap()
+}
+
+async fn sign_in(service: &AuthService) -> Result<(), ClientError> {
+    service
+        .sign_in(
+            EmailOrPhone::Email("alice@example.com".to_string()),
+            "password",
+            None,
+        )
+        .await
+        .map(|_| ())
+}
+
+#[test(tokio::test)]
+async fn fails_fast_while_open() {
+    // Arrange
+    let transport = TimingOutTransport::default();
+    let circuit_breaker = CircuitBreaker::new(
+        CircuitBreakerConfig::builder()
+            .minimum_calls(2)
+            .open_duration(Duration::from_secs(60))
+            .build(),
+    );
+    let service = service(transport.clone(), circuit_breaker.clone());
+
+    // Act
+    let first = sign_in(&service).await;
+    let second = sign_in(&service).await;
+    let third = sign_in(&service).await;
+
+    // Assert
+    assert_matches!(first, Err(ClientError::InternalError));
+    assert_matches!(second, Err(ClientError::InternalError));
+    assert_matches!(third, Err(ClientError::Unavailable));
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    assert_eq!(circuit_breaker.state(), CircuitState::Open);
+    assert!(!circuit_breaker.is_available());
+}
+
+#[test(tokio::test)]
+async fn probes_after_open_duration() {
+    // Arrange
+    let transport = TimingOutTransport::default();
+    let circuit_breaker = CircuitBreaker::new(
+        CircuitBreakerConfig::builder()
+            .minimum_calls(1)
+            .open_duration(Duration::from_millis(50))
+            .build(),
+    );
+    let service = service(transport.clone(), circuit_breaker.clone());
+    let _ = sign_in(&service).await;
+
+    // Act
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let state = circuit_breaker.state();
+    let probe = sign_in(&service).await;
+
+    // Assert
+    assert_eq!(state, CircuitState::HalfOpen);
+    assert_matches!(probe, Err(ClientError::InternalError));
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    assert_eq!(circuit_breaker.state(), CircuitState::Open);
+}
+
+#[test(tokio::test)]
+async fn auth_state_reports_breaker_of_service() {
+    // Arrange
+    let config = SupabaseAuthConfig::builder()
+        .jwt_secret("secret".to_string())
+        .api_url("http://gotrue.test/".parse().unwrap())
+        .api_key("api_key".to_string())
+        .transport(TimingOutTransport::default())
+        .retry_policy(RetryPolicy::none())
+        .circuit_breaker(CircuitBreaker::new(
+            CircuitBreakerConfig::builder()
+                .minimum_calls(2)
+                .open_duration(Duration::from_secs(60))
+                .build(),
+        ))
+        .build();
+    let state = SupabaseAuth::<DefaultAuthTypes>::new(config)
+        .expect("invalid config")
+        .state();
+
+    // Act
+    for _ in 0..2 {
+        let _ = state
+            .auth()
+            .sign_in(
+                EmailOrPhone::Email("alice@example.com".to_string()),
+                "password",
+                None,
+            )
+            .await;
+    }
+
+    // Assert
+    let circuit_breaker = state.circuit_breaker().expect("no circuit breaker");
+    assert_eq!(circuit_breaker.state(), CircuitState::Open);
+}
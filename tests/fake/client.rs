@@ -0,0 +1,62 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_supabase_auth::api::{Api, ApiClient};
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use tokio::net::TcpListener;
+
+/// Serves `/health` only to requests carrying the expected headers.
+async fn health(headers: HeaderMap) -> impl IntoResponse {
+    let expected = [
+        ("apikey", "api_key"),
+        ("x-client-info", "my-app/1.0"),
+        (header::USER_AGENT.as_str(), "custom-agent"),
+    ];
+    let matches = expected
+        .iter()
+        .all(|(name, value)| headers.get(*name).is_some_and(|header| header == value));
+    if !matches {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    Json(json!({
+        "version": "test",
+        "name": "GoTrue",
+        "description": "Header checking GoTrue",
+    }))
+    .into_response()
+}
+
+#[test(tokio::test)]
+async fn injected_client_and_headers() {
+    // Arrange
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let router = Router::new().route("/health", get(health));
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let http_client = reqwest::Client::builder()
+        .user_agent("custom-agent")
+        .timeout(Duration::from_secs(1))
+        .build()
+        .unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-client-info", HeaderValue::from_static("my-app/1.0"));
+
+    let client: ApiClient = ApiClient::builder()
+        .url(url.parse().unwrap())
+        .api_key("api_key")
+        .transport(http_client)
+        .headers(headers)
+        .build()
+        .expect("invalid client configuration");
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert!(result.is_ok(), "{result:?}");
+}
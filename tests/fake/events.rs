@@ -0,0 +1,295 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use axum_supabase_auth::middleware::{AuthClaims, AuthEvents, AuthMethod, RequestMeta};
+use axum_supabase_auth::test_util::FakeGoTrue;
+use axum_supabase_auth::{
+    ClientError, DefaultAuthTypes, LogoutScope, SupabaseAuth, SupabaseAuthConfig, User,
+};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+use tower::ServiceExt;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    SignedIn {
+        email: String,
+        method: AuthMethod,
+        user_agent: Option<String>,
+    },
+    SignInFailed {
+        method: AuthMethod,
+        email: Option<String>,
+    },
+    SignedOut {
+        email: String,
+        scope: LogoutScope,
+    },
+    Refreshed {
+        email: String,
+    },
+}
+
+#[derive(Clone, Default)]
+struct RecordingEvents(Arc<Mutex<Vec<Event>>>);
+
+impl RecordingEvents {
+    fn take(&self) -> Vec<Event> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+impl AuthEvents for RecordingEvents {
+    async fn signed_in(&self, user: &User, method: &AuthMethod, request: &RequestMeta) {
+        self.0.lock().unwrap().push(Event::SignedIn {
+            email: user.email.clone(),
+            method: method.clone(),
+            user_agent: request.user_agent.clone(),
+        });
+    }
+
+    async fn sign_in_failed(
+        &self,
+        method: &AuthMethod,
+        email: Option<&str>,
+        _error: &ClientError,
+        _request: &RequestMeta,
+    ) {
+        self.0.lock().unwrap().push(Event::SignInFailed {
+            method: method.clone(),
+            email: email.map(str::to_string),
+        });
+    }
+
+    async fn signed_out(
+        &self,
+        claims: &AuthClaims<DefaultAuthTypes>,
+        scope: LogoutScope,
+        _request: &RequestMeta,
+    ) {
+        self.0.lock().unwrap().push(Event::SignedOut {
+            email: claims.email.clone(),
+            scope,
+        });
+    }
+
+    async fn refreshed(&self, user: &User, _request: &RequestMeta) {
+        self.0.lock().unwrap().push(Event::Refreshed {
+            email: user.email.clone(),
+        });
+    }
+}
+
+fn app(gotrue: &FakeGoTrue, events: RecordingEvents) -> Router {
+    let config = SupabaseAuthConfig::builder()
+        .jwt_secret(gotrue.jwt_secret().to_string())
+        .api_url(gotrue.url().clone())
+        .api_key(gotrue.api_key().to_string())
+        .build();
+    let auth = SupabaseAuth::<DefaultAuthTypes>::new(config)
+        .expect("invalid config")
+        .with_events(events);
+
+    auth.router().with_state(auth.state())
+}
+
+fn form(uri: &str, body: &str) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(header::USER_AGENT, "events-test")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Returns the session cookies set by `response` as a `Cookie` header value.
+fn session_cookies(response: &Response) -> String {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .filter(|cookie| cookie.starts_with("sb-auth=") || cookie.starts_with("sb-refresh="))
+        .filter_map(|cookie| cookie.split(';').next())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+async fn sign_up(app: &Router) {
+    let response = app
+        .clone()
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+}
+
+#[test(tokio::test)]
+async fn login_emits_signed_in() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let events = RecordingEvents::default();
+    let app = app(&gotrue, events.clone());
+    sign_up(&app).await;
+    events.take();
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/login",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        events.take(),
+        vec![Event::SignedIn {
+            email: "alice@example.com".to_string(),
+            method: AuthMethod::Password,
+            user_agent: Some("events-test".to_string()),
+        }]
+    );
+}
+
+#[test(tokio::test)]
+async fn failed_login_emits_sign_in_failed() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let events = RecordingEvents::default();
+    let app = app(&gotrue, events.clone());
+    sign_up(&app).await;
+    events.take();
+
+    // Act
+    let response = app
+        .oneshot(form("/login", "email=alice%40example.com&password=wrong"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        events.take(),
+        vec![Event::SignInFailed {
+            method: AuthMethod::Password,
+            email: Some("alice@example.com".to_string()),
+        }]
+    );
+}
+
+#[test(tokio::test)]
+async fn failed_signup_emits_sign_in_failed() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let events = RecordingEvents::default();
+    let app = app(&gotrue, events.clone());
+    sign_up(&app).await;
+    events.take();
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        events.take(),
+        vec![Event::SignInFailed {
+            method: AuthMethod::Password,
+            email: Some("alice@example.com".to_string()),
+        }]
+    );
+}
+
+#[test(tokio::test)]
+async fn logout_emits_signed_out() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let events = RecordingEvents::default();
+    let app = app(&gotrue, events.clone());
+    sign_up(&app).await;
+    events.take();
+    let response = app
+        .clone()
+        .oneshot(form(
+            "/login",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+    let cookies = session_cookies(&response);
+    events.take();
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::post("/logout?scope=local")
+                .header(header::COOKIE, cookies)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        events.take(),
+        vec![Event::SignedOut {
+            email: "alice@example.com".to_string(),
+            scope: LogoutScope::Local,
+        }]
+    );
+}
+
+#[test(tokio::test)]
+async fn refresh_emits_refreshed() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let events = RecordingEvents::default();
+    let app = app(&gotrue, events.clone());
+    sign_up(&app).await;
+    let response = app
+        .clone()
+        .oneshot(form(
+            "/login",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+    let cookies = session_cookies(&response);
+    events.take();
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::post("/refresh?next=%2Fprofile")
+                .header(header::COOKIE, cookies)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[header::LOCATION], "/profile");
+    assert!(session_cookies(&response).contains("sb-refresh="));
+    assert_eq!(
+        events.take(),
+        vec![Event::Refreshed {
+            email: "alice@example.com".to_string(),
+        }]
+    );
+}
@@ -0,0 +1,263 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use axum_supabase_auth::api::SignUpOptions;
+use axum_supabase_auth::middleware::Empty;
+use axum_supabase_auth::test_util::FakeGoTrue;
+use axum_supabase_auth::{
+    Auth, AuthService, AuthTypes, DefaultAuthTypes, EmailOrPhone, SupabaseAuth, SupabaseAuthConfig,
+};
+use serde::{Deserialize, Serialize};
+use test_log::test;
+use tower::ServiceExt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+    age: u32,
+    newsletter: bool,
+}
+
+struct ProfileTypes;
+
+impl AuthTypes for ProfileTypes {
+    type AppData = Empty;
+    type UserData = Profile;
+    type AdditionalData = Empty;
+}
+
+fn auth<T: AuthTypes>(gotrue: &FakeGoTrue) -> SupabaseAuth<T> {
+    let config = SupabaseAuthConfig::builder()
+        .jwt_secret(gotrue.jwt_secret().to_string())
+        .api_url(gotrue.url().clone())
+        .api_key(gotrue.api_key().to_string())
+        .build();
+    SupabaseAuth::new(config).expect("invalid config")
+}
+
+fn app(gotrue: &FakeGoTrue) -> Router {
+    let auth = auth::<DefaultAuthTypes>(gotrue);
+
+    auth.router().with_state(auth.state())
+}
+
+fn auth_cookie(response: &Response) -> String {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .find_map(|cookie| cookie.strip_prefix("sb-auth="))
+        .and_then(|cookie| cookie.split(';').next())
+        .expect("no auth cookie")
+        .to_string()
+}
+
+fn form(uri: &str, body: &str) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[test(tokio::test)]
+async fn signup_form_with_autoconfirm() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let app = app(&gotrue);
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password&data=%7B%22name%22%3A%22Alice%22%7D",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[header::LOCATION], "/profile");
+    let cookies: Vec<_> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .collect();
+    assert!(cookies.iter().any(|cookie| cookie.starts_with("sb-auth=")));
+    assert!(cookies
+        .iter()
+        .any(|cookie| cookie.starts_with("sb-refresh=")));
+}
+
+#[test(tokio::test)]
+async fn signup_form_requires_confirmation() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().start().await;
+    let app = app(&gotrue);
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "/login?confirmation=sent"
+    );
+    assert!(gotrue.otp("alice@example.com").is_some());
+}
+
+#[test(tokio::test)]
+async fn login_form() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let app = app(&gotrue);
+    app.clone()
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password",
+        ))
+        .await
+        .unwrap();
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/login",
+            "email=alice%40example.com&password=password&next=%2Fdashboard",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[header::LOCATION], "/dashboard");
+}
+
+#[test(tokio::test)]
+async fn signup_form_weak_password() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let app = app(&gotrue);
+
+    // Act
+    let response = app
+        .oneshot(form("/signup", "email=alice%40example.com&password=short"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[test(tokio::test)]
+async fn signup_with_typed_user_data() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let auth = auth::<ProfileTypes>(&gotrue);
+    let app = auth.router().with_state(auth.state());
+    let expected = Profile {
+        name: "Alice".to_string(),
+        age: 30,
+        newsletter: true,
+    };
+
+    // Act
+    let json = app
+        .clone()
+        .oneshot(
+            Request::post("/signup")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    r#"{"email":"alice@example.com","password":"password",
+                        "data":{"name":"Alice","age":30,"newsletter":true}}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let form = app
+        .oneshot(form(
+            "/signup",
+            "email=bob%40example.com&password=password\
+             &data=%7B%22name%22%3A%22Alice%22%2C%22age%22%3A30%2C%22newsletter%22%3Atrue%7D",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    for response in [json, form] {
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let claims = auth.state().decode(&auth_cookie(&response)).unwrap();
+        assert_eq!(claims.user_metadata, expected);
+    }
+}
+
+#[test(tokio::test)]
+async fn session_has_typed_user() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let service: AuthService<ProfileTypes> = gotrue.auth_service();
+    let expected = Profile {
+        name: "Alice".to_string(),
+        age: 30,
+        newsletter: true,
+    };
+    let options = SignUpOptions::<ProfileTypes>::builder()
+        .data(expected.clone())
+        .build();
+
+    // Act
+    let signed_up = service
+        .sign_up(
+            EmailOrPhone::Email("alice@example.com".to_string()),
+            "password",
+            options,
+        )
+        .await
+        .unwrap()
+        .session()
+        .expect("no session");
+    let signed_in = service
+        .sign_in(
+            EmailOrPhone::Email("alice@example.com".to_string()),
+            "password",
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(signed_up.user.user_metadata, expected);
+    assert_eq!(signed_in.user.user_metadata, expected);
+}
+
+#[test(tokio::test)]
+async fn signup_form_ignores_unknown_fields() {
+    // Arrange
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let auth = auth::<DefaultAuthTypes>(&gotrue);
+    let app = auth.router().with_state(auth.state());
+
+    // Act
+    let response = app
+        .oneshot(form(
+            "/signup",
+            "email=alice%40example.com&password=password&csrf_token=abc&submit=Sign+up",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let claims = auth.state().decode(&auth_cookie(&response)).unwrap();
+    assert_eq!(claims.user_metadata, serde_json::json!({}));
+}
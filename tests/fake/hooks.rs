@@ -0,0 +1,238 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_supabase_auth::hooks::{
+    CustomAccessTokenOutput, HookDecision, HookError, HooksRouter,
+    PasswordVerificationAttemptOutput, WebhookVerifier, WEBHOOK_ID, WEBHOOK_SIGNATURE,
+    WEBHOOK_TIMESTAMP,
+};
+use axum_supabase_auth::{AuthTypes, DefaultAuthTypes};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use test_log::test;
+use time::OffsetDateTime;
+use tower::ServiceExt;
+
+const SECRET: &str = "v1,whsec_c2VjcmV0LXNpZ25pbmcta2V5";
+
+fn app() -> Router {
+    let verifier = WebhookVerifier::new(SECRET).unwrap();
+    let hooks = HooksRouter::<DefaultAuthTypes>::new(verifier)
+        .custom_access_token(|mut input| async move {
+            input.claims.claims.additional["tier"] = json!("pro");
+            Ok(CustomAccessTokenOutput {
+                claims: input.claims,
+            })
+        })
+        .password_verification_attempt(|input| async move {
+            if input.valid {
+                return Err(HookError::new(StatusCode::FORBIDDEN, "Sign-in disabled"));
+            }
+            Ok(PasswordVerificationAttemptOutput {
+                decision: HookDecision::Reject,
+                message: Some("Too many attempts".to_string()),
+                should_logout_user: true,
+            })
+        });
+
+    Router::new().nest("/hooks", hooks.router())
+}
+
+fn signed(uri: &str, body: Value) -> Request<Body> {
+    let body = body.to_string();
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let signature = WebhookVerifier::new(SECRET)
+        .unwrap()
+        .sign("msg_1", timestamp, body.as_bytes());
+
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(WEBHOOK_ID, "msg_1")
+        .header(WEBHOOK_TIMESTAMP, timestamp.to_string())
+        .header(WEBHOOK_SIGNATURE, signature)
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn json_body(response: axum::response::Response) -> Value {
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn access_token_input() -> Value {
+    json!({
+        "user_id": "8d3cd4d8-8d5b-4b8e-9d0a-6d2c9d6a4f3e",
+        "claims": {
+            "sub": "8d3cd4d8-8d5b-4b8e-9d0a-6d2c9d6a4f3e",
+            "email": "alice@example.com",
+            "phone": "",
+            "exp": 1723976869,
+            "role": "authenticated",
+            "aal": "aal1",
+            "app_metadata": { "provider": "email", "providers": ["email"] },
+            "user_metadata": {},
+            "is_anonymous": false
+        },
+        "authentication_method": "password"
+    })
+}
+
+#[test(tokio::test)]
+async fn custom_access_token_hook() {
+    // Arrange
+    let app = app();
+
+    // Act
+    let response = app
+        .oneshot(signed("/hooks/custom-access-token", access_token_input()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["claims"]["tier"], "pro");
+    assert_eq!(body["claims"]["aal"], "aal1");
+    assert_eq!(body["claims"]["email"], "alice@example.com");
+}
+
+#[test(tokio::test)]
+async fn password_verification_attempt_hook() {
+    // Arrange
+    let app = app();
+    let user_id = "8d3cd4d8-8d5b-4b8e-9d0a-6d2c9d6a4f3e";
+
+    // Act
+    let rejected = app
+        .clone()
+        .oneshot(signed(
+            "/hooks/password-verification-attempt",
+            json!({ "user_id": user_id, "valid": false }),
+        ))
+        .await
+        .unwrap();
+    let failed = app
+        .oneshot(signed(
+            "/hooks/password-verification-attempt",
+            json!({ "user_id": user_id, "valid": true }),
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(rejected.status(), StatusCode::OK);
+    assert_eq!(
+        json_body(rejected).await,
+        json!({ "decision": "reject", "message": "Too many attempts", "should_logout_user": true })
+    );
+    assert_eq!(failed.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        json_body(failed).await,
+        json!({ "error": { "http_code": 403, "message": "Sign-in disabled" } })
+    );
+}
+
+#[test(tokio::test)]
+async fn rejects_invalid_signature() {
+    // Arrange
+    let app = app();
+    let mut request = signed("/hooks/custom-access-token", access_token_input());
+    request
+        .headers_mut()
+        .insert(WEBHOOK_SIGNATURE, "v1,c2lnbmF0dXJl".parse().unwrap());
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test(tokio::test)]
+async fn unregistered_hook_not_found() {
+    // Arrange
+    let app = app();
+
+    // Act
+    let response = app
+        .oneshot(signed(
+            "/hooks/send-sms",
+            json!({ "user": {}, "sms": { "otp": "123456" } }),
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Roles {
+    roles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Tier {
+    #[serde(default)]
+    tier: String,
+}
+
+struct TypedAuth;
+
+impl AuthTypes for TypedAuth {
+    type AppData = Roles;
+    type UserData = Profile;
+    type AdditionalData = Tier;
+}
+
+#[test(tokio::test)]
+async fn custom_access_token_keeps_unknown_claims() {
+    // Arrange
+    let verifier = WebhookVerifier::new(SECRET).unwrap();
+    let hooks =
+        HooksRouter::<TypedAuth>::new(verifier).custom_access_token(|mut input| async move {
+            input.claims.claims.additional.tier = "pro".to_string();
+            Ok(CustomAccessTokenOutput {
+                claims: input.claims,
+            })
+        });
+    let app: Router = Router::new().nest("/hooks", hooks.router());
+    let claims = json!({
+        "iss": "http://127.0.0.1:54321/auth/v1",
+        "aud": "authenticated",
+        "exp": 1723976869,
+        "iat": 1723973269,
+        "sub": "8d3cd4d8-8d5b-4b8e-9d0a-6d2c9d6a4f3e",
+        "email": "alice@example.com",
+        "phone": "",
+        "role": "authenticated",
+        "aal": "aal1",
+        "amr": [{ "method": "password", "timestamp": 1723973269 }],
+        "session_id": "9994a598-3263-40e4-b1c0-59a1483a84e2",
+        "app_metadata": { "provider": "email", "providers": ["email"], "roles": ["admin"] },
+        "user_metadata": { "name": "Alice" },
+        "is_anonymous": false
+    });
+    let input = json!({
+        "user_id": "8d3cd4d8-8d5b-4b8e-9d0a-6d2c9d6a4f3e",
+        "claims": claims,
+        "authentication_method": "password"
+    });
+
+    // Act
+    let response = app
+        .oneshot(signed("/hooks/custom-access-token", input))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut expected = claims;
+    expected["tier"] = json!("pro");
+    assert_eq!(json_body(response).await, json!({ "claims": expected }));
+}
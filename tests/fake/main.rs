@@ -0,0 +1,14 @@
+mod api;
+mod circuit_breaker;
+mod client;
+mod events;
+mod handlers;
+#[cfg(feature = "hooks")]
+mod hooks;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mock;
+mod retry;
+mod settings;
+mod token;
+mod transport;
@@ -0,0 +1,127 @@
+use axum_supabase_auth::api::{Api, ApiClient, SignUpOptions};
+use axum_supabase_auth::metrics::{API_REQUESTS, REFRESHES, TOKEN_VALIDATIONS};
+use axum_supabase_auth::middleware::Decoder;
+use axum_supabase_auth::test_util::{FakeGoTrue, TestToken};
+use axum_supabase_auth::{Auth, AuthService, DefaultAuthTypes, EmailOrPhone, SessionAuth};
+use jsonwebtoken::EncodingKey;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use test_log::test;
+use time::{Duration, OffsetDateTime};
+
+const EMAIL: &str = "alice@example.com";
+const PASSWORD: &str = "password";
+
+/// Returns the value of the counter `name` with all of `labels`.
+fn counter(snapshotter: &Snapshotter, name: &str, labels: &[(&str, &str)]) -> u64 {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter(|(key, ..)| key.key().name() == name)
+        .filter(|(key, ..)| {
+            labels.iter().all(|(label, value)| {
+                key.key()
+                    .labels()
+                    .any(|l| l.key() == *label && l.value() == *value)
+            })
+        })
+        .map(|(.., value)| match value {
+            DebugValue::Counter(value) => value,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[test(tokio::test)]
+async fn api_calls_are_counted() {
+    // Arrange
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let client: ApiClient = gotrue.api_client();
+
+    // Act
+    let _ = client
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await;
+
+    // Assert
+    let failed = counter(
+        &snapshotter,
+        API_REQUESTS,
+        &[
+            ("method", "POST"),
+            ("endpoint", "token"),
+            ("status", "400"),
+            ("error_code", "invalid_credentials"),
+        ],
+    );
+    assert_eq!(failed, 1);
+}
+
+#[test(tokio::test)]
+async fn refreshes_are_counted() {
+    // Arrange
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+    let gotrue = FakeGoTrue::builder().autoconfirm(true).start().await;
+    let auth: AuthService = gotrue.auth_service();
+    let client: ApiClient = gotrue.api_client();
+    client
+        .sign_up(
+            EmailOrPhone::Email(EMAIL.to_string()),
+            PASSWORD,
+            &SignUpOptions::new(),
+        )
+        .await
+        .unwrap();
+    let session = auth
+        .sign_in(EmailOrPhone::Email(EMAIL.to_string()), PASSWORD, None)
+        .await
+        .unwrap();
+
+    // Act
+    let result = auth
+        .with_refresh_token(session.access_token, session.refresh_token)
+        .refresh()
+        .await;
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(
+        counter(&snapshotter, REFRESHES, &[("outcome", "success")]),
+        1
+    );
+}
+
+#[test]
+fn token_validations_are_counted() {
+    // Arrange
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let _guard = metrics::set_default_local_recorder(&recorder);
+    let decoder = Decoder::<DefaultAuthTypes>::new("secret");
+    let valid: TestToken = TestToken::builder()
+        .key(EncodingKey::from_secret(b"secret"))
+        .build();
+    let expired: TestToken = TestToken::builder()
+        .key(EncodingKey::from_secret(b"secret"))
+        .expires_at(OffsetDateTime::now_utc() - Duration::hours(1))
+        .build();
+    let forged: TestToken = TestToken::builder()
+        .key(EncodingKey::from_secret(b"other secret"))
+        .build();
+
+    // Act
+    let _ = decoder.decode(&valid.access_token());
+    let _ = decoder.decode(&expired.access_token());
+    let _ = decoder.decode(&forged.access_token());
+
+    // Assert
+    for outcome in ["valid", "expired", "bad_signature"] {
+        let count = counter(&snapshotter, TOKEN_VALIDATIONS, &[("outcome", outcome)]);
+        assert_eq!(count, 1, "{outcome}");
+    }
+}
@@ -0,0 +1,111 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use axum_supabase_auth::test_util::{MockAuth, MockCall};
+use axum_supabase_auth::{
+    ClientError, DefaultAuthTypes, EmailOrPhone, Session, SupabaseAuth, SupabaseAuthConfig,
+};
+use matches::assert_matches;
+use serde_json::json;
+use test_log::test;
+use tower::ServiceExt;
+
+fn app(auth: MockAuth) -> Router {
+    let config = SupabaseAuthConfig::builder()
+        .jwt_secret("secret".to_string())
+        .api_url("http://localhost:9999".try_into().unwrap())
+        .api_key("api_key".to_string())
+        .build();
+    let auth = SupabaseAuth::<DefaultAuthTypes>::with_auth(config, auth).expect("invalid config");
+
+    auth.router().with_state(auth.state())
+}
+
+fn session() -> Session {
+    serde_json::from_value(json!({
+        "access_token": "access-token",
+        "token_type": "bearer",
+        "expires_in": 3600,
+        "expires_at": 4102444800u64,
+        "refresh_token": "refresh-token",
+        "user": {
+            "id": "34abc1f7-e346-4b30-bc26-1b53f707bf54",
+            "aud": "authenticated",
+            "role": "authenticated",
+            "email": "alice@example.com",
+            "app_metadata": { "provider": "email", "providers": ["email"] },
+            "user_metadata": {},
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        }
+    }))
+    .expect("invalid session")
+}
+
+fn login_form(body: &str) -> Request<Body> {
+    Request::post("/login")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[test(tokio::test)]
+async fn login_sets_cookies() {
+    // Arrange
+    let auth = MockAuth::new();
+    auth.sign_in_returns(Ok(session()));
+    let app = app(auth.clone());
+
+    // Act
+    let response = app
+        .oneshot(login_form(
+            "email=alice%40example.com&password=password&captcha_token=captcha",
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let cookies: Vec<_> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .collect();
+    assert!(cookies
+        .iter()
+        .any(|cookie| cookie.starts_with("sb-auth=access-token")));
+    assert!(cookies
+        .iter()
+        .any(|cookie| cookie.starts_with("sb-refresh=refresh-token")));
+
+    let calls = auth.calls();
+    assert_eq!(calls.len(), 1);
+    assert_matches!(
+        &calls[0],
+        MockCall::SignIn {
+            email_or_phone: EmailOrPhone::Email(email),
+            password,
+            captcha_token: Some(captcha_token),
+        } if email == "alice@example.com" && password == "password" && captcha_token == "captcha"
+    );
+}
+
+#[test(tokio::test)]
+async fn login_wrong_credentials() {
+    // Arrange
+    let auth = MockAuth::new();
+    auth.sign_in_returns(Err(ClientError::WrongCredentials));
+    let app = app(auth.clone());
+
+    // Act
+    let response = app
+        .oneshot(login_form("email=alice%40example.com&password=wrong"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(!response.status().is_redirection());
+    assert!(response.headers().get(header::SET_COOKIE).is_none());
+    assert_eq!(auth.calls().len(), 1);
+}
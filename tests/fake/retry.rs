@@ -0,0 +1,198 @@
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use axum_supabase_auth::api::{
+    Api, ApiClient, ApiError, ApiErrorCode, ResendRequest, ResendType, RetryPolicy,
+};
+use axum_supabase_auth::{Auth, AuthService, ClientError, EmailOrPhone};
+use matches::assert_matches;
+use serde_json::json;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use test_log::test;
+use tokio::net::TcpListener;
+
+/// Serves `/health` and `/admin/users`, failing with `failure` for the first `failures`
+/// requests.
+async fn spawn_flaky(
+    failures: u32,
+    failure: fn() -> axum::response::Response,
+) -> (ApiClient, Arc<AtomicU32>) {
+    let requests = Arc::new(AtomicU32::new(0));
+    let flaky = |body: fn() -> serde_json::Value| {
+        let counter = requests.clone();
+        get(move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    return failure();
+                }
+
+                Json(body()).into_response()
+            }
+        })
+    };
+    let router = Router::new()
+        .route(
+            "/health",
+            flaky(|| {
+                json!({
+                    "version": "test",
+                    "name": "GoTrue",
+                    "description": "Flaky GoTrue",
+                })
+            }),
+        )
+        .route("/admin/users", flaky(|| json!({ "users": [] })));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let client = ApiClient::new(url.parse().unwrap(), Duration::from_secs(1), "api_key")
+        .with_retry_policy(
+            RetryPolicy::builder()
+                .initial_backoff(Duration::from_millis(1))
+                .build(),
+        );
+
+    (client, requests)
+}
+
+fn unavailable() -> axum::response::Response {
+    StatusCode::SERVICE_UNAVAILABLE.into_response()
+}
+
+fn rate_limited() -> axum::response::Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, "0")],
+        Json(json!({
+            "code": 429,
+            "error_code": "over_request_rate_limit",
+            "msg": "Request rate limit reached",
+        })),
+    )
+        .into_response()
+}
+
+fn rate_limited_long() -> axum::response::Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, "3600")],
+    )
+        .into_response()
+}
+
+#[test(tokio::test)]
+async fn retries_server_errors() {
+    // Arrange
+    let (client, requests) = spawn_flaky(2, unavailable).await;
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[test(tokio::test)]
+async fn retries_listing_users() {
+    // Arrange
+    let (client, requests) = spawn_flaky(1, unavailable).await;
+
+    // Act
+    let result = client.list_users("service_role_key").await;
+
+    // Assert
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[test(tokio::test)]
+async fn gives_up_after_max_retries() {
+    // Arrange
+    let (client, requests) = spawn_flaky(10, unavailable).await;
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert_matches!(result, Err(ApiError::UnknownHTTP(_)));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[test(tokio::test)]
+async fn retries_after_rate_limit() {
+    // Arrange
+    let (client, requests) = spawn_flaky(1, rate_limited).await;
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert!(result.is_ok());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[test(tokio::test)]
+async fn long_retry_after_is_returned() {
+    // Arrange
+    let (client, requests) = spawn_flaky(1, rate_limited_long).await;
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(ApiError::RateLimited {
+            code: ApiErrorCode::OverRequestRateLimit,
+            retry_after: Some(retry_after),
+        }) if retry_after == Duration::from_secs(3600)
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+/// Resends a signup confirmation against a GoTrue rate limiting it with `error_code`.
+async fn resend_rate_limited(error_code: &'static str) -> Result<(), ClientError> {
+    let router = Router::new().route(
+        "/resend",
+        post(move || async move {
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({ "code": 429, "error_code": error_code, "msg": "Rate limited" })),
+            )
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let service: AuthService = AuthService::builder()
+        .url(url.parse().unwrap())
+        .api_key("api_key")
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+    let request = ResendRequest::builder()
+        .kind(ResendType::Signup)
+        .target(EmailOrPhone::Email("alice@example.com".to_string()))
+        .build();
+
+    service.resend(request).await
+}
+
+#[test(tokio::test)]
+async fn rate_limit_keeps_error_code() {
+    // Act
+    let email = resend_rate_limited("over_email_send_rate_limit").await;
+    let request = resend_rate_limited("over_request_rate_limit").await;
+
+    // Assert
+    assert_matches!(email, Err(ClientError::EmailRateLimited));
+    assert_matches!(request, Err(ClientError::RateLimited { .. }));
+}
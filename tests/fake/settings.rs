@@ -0,0 +1,123 @@
+use axum::body::Bytes;
+use axum::http::{header, Request, Response, StatusCode};
+use axum_supabase_auth::api::{HttpTransport, RetryPolicy, Settings, TransportError};
+use axum_supabase_auth::middleware::AuthState;
+use axum_supabase_auth::test_util::{MockAuth, MockCall};
+use axum_supabase_auth::{ClientError, DefaultAuthTypes, SupabaseAuth, SupabaseAuthConfig};
+use futures_util::future::join_all;
+use matches::assert_matches;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use test_log::test;
+
+fn settings_json() -> serde_json::Value {
+    json!({
+        "external": { "github": true, "email": true },
+        "disable_signup": false,
+        "mailer_autoconfirm": false,
+        "phone_autoconfirm": false,
+        "sms_provider": "",
+    })
+}
+
+fn settings() -> Settings {
+    serde_json::from_value(settings_json()).unwrap()
+}
+
+fn config(settings_ttl: Duration) -> SupabaseAuthConfig {
+    SupabaseAuthConfig::builder()
+        .jwt_secret("secret".to_string())
+        .api_url("http://gotrue.test/auth/v1/".parse().unwrap())
+        .api_key("api_key".to_string())
+        .settings_ttl(settings_ttl)
+        .retry_policy(RetryPolicy::none())
+        .build()
+}
+
+fn state(auth: &MockAuth, settings_ttl: Duration) -> AuthState<DefaultAuthTypes> {
+    SupabaseAuth::with_auth(config(settings_ttl), auth.clone())
+        .expect("invalid config")
+        .state()
+}
+
+fn settings_calls(auth: &MockAuth) -> usize {
+    auth.calls()
+        .iter()
+        .filter(|call| matches!(call, MockCall::Settings))
+        .count()
+}
+
+/// Responds to every request with the settings after a delay, counting the requests.
+#[derive(Clone, Default)]
+struct SlowSettingsTransport {
+    requests: Arc<AtomicUsize>,
+}
+
+impl HttpTransport for SlowSettingsTransport {
+    async fn send(&self, _request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Bytes::from(settings_json().to_string()))
+            .unwrap())
+    }
+}
+
+#[test(tokio::test)]
+async fn concurrent_misses_share_one_fetch() {
+    // Arrange
+    let transport = SlowSettingsTransport::default();
+    let mut config = config(Duration::from_secs(60));
+    config.transport = Some(transport.clone().into());
+    let state = SupabaseAuth::<DefaultAuthTypes>::new(config)
+        .expect("invalid config")
+        .state();
+
+    // Act
+    let results = join_all((0..10).map(|_| state.settings())).await;
+
+    // Assert
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+}
+
+#[test(tokio::test)]
+async fn stale_settings_are_used_while_fetching_fails() {
+    // Arrange
+    let auth = MockAuth::new();
+    auth.settings_returns(Ok(settings()))
+        .settings_returns(Err(ClientError::InternalError));
+    let state = state(&auth, Duration::ZERO);
+    state.settings().await.unwrap();
+
+    // Act
+    let failed = state.settings().await;
+    let retried = state.settings().await;
+
+    // Assert
+    assert!(failed.unwrap().is_provider_enabled("github"));
+    assert!(retried.unwrap().is_provider_enabled("github"));
+    assert_eq!(settings_calls(&auth), 2);
+}
+
+#[test(tokio::test)]
+async fn failed_fetch_is_cached() {
+    // Arrange
+    let auth = MockAuth::new();
+    auth.settings_returns(Err(ClientError::InternalError));
+    let state = state(&auth, Duration::from_secs(60));
+
+    // Act
+    let failed = state.settings().await;
+    let retried = state.settings().await;
+
+    // Assert
+    assert_matches!(failed, Err(ClientError::InternalError));
+    assert_matches!(retried, Err(ClientError::Unavailable));
+    assert_eq!(settings_calls(&auth), 1);
+}
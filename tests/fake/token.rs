@@ -0,0 +1,162 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_supabase_auth::middleware::{AuthState, User};
+use axum_supabase_auth::test_util::{MockAuth, TestToken};
+use axum_supabase_auth::{AuthTypes, SupabaseAuth, SupabaseAuthConfig};
+use jsonwebtoken::EncodingKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use test_log::test;
+use time::OffsetDateTime;
+use tower::ServiceExt;
+
+const SECRET: &str = "secret";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Roles {
+    roles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+}
+
+struct TypedAuth;
+
+impl AuthTypes for TypedAuth {
+    type AppData = Roles;
+    type UserData = Profile;
+    type AdditionalData = Value;
+}
+
+async fn me(User(claims): User<TypedAuth>) -> Json<Value> {
+    Json(json!({
+        "sub": claims.sub,
+        "email": claims.email,
+        "roles": claims.app_metadata.additional.roles,
+        "name": claims.user_metadata.name,
+    }))
+}
+
+fn app() -> Router {
+    let config = SupabaseAuthConfig::builder()
+        .jwt_secret(SECRET.to_string())
+        .api_url("http://localhost:9999".try_into().unwrap())
+        .api_key("api_key".to_string())
+        .build();
+    let auth =
+        SupabaseAuth::<TypedAuth>::with_auth(config, MockAuth::default()).expect("invalid config");
+
+    Router::<AuthState<TypedAuth>>::new()
+        .route("/me", get(me))
+        .with_state(auth.state())
+}
+
+fn request(token: &TestToken<TypedAuth>) -> Request<Body> {
+    Request::get("/me")
+        .header(header::COOKIE, token.cookie_header("sb-auth"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[test(tokio::test)]
+async fn typed_claims_from_cookie() {
+    // Arrange
+    let token = TestToken::<TypedAuth>::builder()
+        .key(EncodingKey::from_secret(SECRET.as_bytes()))
+        .email("alice@example.com")
+        .app_metadata(Roles {
+            roles: vec!["admin".to_string()],
+        })
+        .user_metadata(Profile {
+            name: "Alice".to_string(),
+        })
+        .build();
+
+    // Act
+    let response = app().oneshot(request(&token)).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "sub": token.sub(),
+            "email": "alice@example.com",
+            "roles": ["admin"],
+            "name": "Alice",
+        })
+    );
+}
+
+#[test(tokio::test)]
+async fn expired_token_is_rejected() {
+    // Arrange
+    let token = TestToken::<TypedAuth>::builder()
+        .key(EncodingKey::from_secret(SECRET.as_bytes()))
+        .user_metadata(Profile {
+            name: "Alice".to_string(),
+        })
+        .expires_at(OffsetDateTime::now_utc() - time::Duration::hours(1))
+        .build();
+
+    // Act
+    let response = app().oneshot(request(&token)).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn wrong_key_is_rejected() {
+    // Arrange
+    let token = TestToken::<TypedAuth>::builder()
+        .key(EncodingKey::from_secret(b"other-secret"))
+        .user_metadata(Profile {
+            name: "Alice".to_string(),
+        })
+        .build();
+
+    // Act
+    let response = app().oneshot(request(&token)).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn typed_claims_from_authorization_header() {
+    // Arrange
+    let token = TestToken::<TypedAuth>::builder()
+        .key(EncodingKey::from_secret(SECRET.as_bytes()))
+        .email("alice@example.com")
+        .app_metadata(Roles {
+            roles: vec!["admin".to_string()],
+        })
+        .user_metadata(Profile {
+            name: "Alice".to_string(),
+        })
+        .build();
+    let request = Request::get("/me")
+        .header(header::AUTHORIZATION, token.authorization_header())
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app().oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["email"], "alice@example.com");
+}
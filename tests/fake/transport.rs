@@ -0,0 +1,181 @@
+use axum::body::Bytes;
+use axum::http::{header, Method, Request, Response, StatusCode};
+use axum_supabase_auth::api::{
+    Api, ApiClient, ApiError, HttpTransport, RetryPolicy, TransportError, TransportErrorKind,
+};
+use axum_supabase_auth::LogoutScope;
+use matches::assert_matches;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+/// Records all requests and responds with `status` and `body`.
+#[derive(Clone)]
+struct RecordingTransport {
+    requests: Arc<Mutex<Vec<Request<Bytes>>>>,
+    status: StatusCode,
+    body: serde_json::Value,
+}
+
+impl RecordingTransport {
+    fn new(status: StatusCode, body: serde_json::Value) -> Self {
+        Self {
+            requests: Arc::default(),
+            status,
+            body,
+        }
+    }
+}
+
+impl HttpTransport for RecordingTransport {
+    async fn send(&self, request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        self.requests.lock().unwrap().push(request);
+
+        Ok(Response::builder()
+            .status(self.status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Bytes::from(self.body.to_string()))
+            .unwrap())
+    }
+}
+
+/// Fails every request with a connection error.
+struct UnreachableTransport;
+
+impl HttpTransport for UnreachableTransport {
+    async fn send(&self, _request: Request<Bytes>) -> Result<Response<Bytes>, TransportError> {
+        Err(TransportError::new(
+            TransportErrorKind::Connect,
+            "connection refused",
+        ))
+    }
+}
+
+fn client(transport: impl HttpTransport) -> ApiClient {
+    ApiClient::builder()
+        .url("http://gotrue.test/auth/v1/".parse().unwrap())
+        .api_key("api_key")
+        .transport(transport)
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap()
+}
+
+#[test(tokio::test)]
+async fn requests_go_through_transport() {
+    // Arrange
+    let transport = RecordingTransport::new(StatusCode::NO_CONTENT, json!({}));
+    let client = client(transport.clone());
+
+    // Act
+    let result = client.logout("access_token", LogoutScope::Local).await;
+
+    // Assert
+    assert!(result.is_ok(), "{result:?}");
+    let requests = transport.requests.lock().unwrap();
+    let request = &requests[0];
+    assert_eq!(request.method(), Method::POST);
+    assert_eq!(
+        request.uri().to_string(),
+        "http://gotrue.test/auth/v1/logout?scope=local"
+    );
+    assert_eq!(request.headers()["apikey"], "api_key");
+    assert_eq!(
+        request.headers()[header::AUTHORIZATION],
+        "Bearer access_token"
+    );
+}
+
+#[test(tokio::test)]
+async fn json_body_is_sent() {
+    // Arrange
+    let transport = RecordingTransport::new(
+        StatusCode::BAD_REQUEST,
+        json!({ "error": "invalid_grant", "error_description": "Refresh Token Not Found" }),
+    );
+    let client = client(transport.clone());
+
+    // Act
+    let result = client.refresh_access_token("refresh_token").await;
+
+    // Assert
+    assert_matches!(result, Err(ApiError::OAuth(StatusCode::BAD_REQUEST, _, _)));
+    let requests = transport.requests.lock().unwrap();
+    let request = &requests[0];
+    assert_eq!(request.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(request.body()).unwrap(),
+        json!({ "refresh_token": "refresh_token" })
+    );
+}
+
+#[test(tokio::test)]
+async fn transport_errors_are_returned() {
+    // Arrange
+    let client = client(UnreachableTransport);
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    let error = result.unwrap_err();
+    assert!(error.is_transient());
+    assert_matches!(error, ApiError::Transport(e) if e.kind() == TransportErrorKind::Connect);
+}
+
+#[cfg(feature = "tower")]
+#[test(tokio::test)]
+async fn tower_service_transport() {
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use axum_supabase_auth::api::TowerTransport;
+
+    // Arrange
+    let router = Router::new().route(
+        "/auth/v1/health",
+        get(|| async {
+            Json(json!({
+                "version": "test",
+                "name": "GoTrue",
+                "description": "In-process GoTrue",
+            }))
+        }),
+    );
+    let client = client(TowerTransport::new(router));
+
+    // Act
+    let result = client.health_check().await;
+
+    // Assert
+    assert_eq!(result.unwrap().description, "In-process GoTrue");
+}
+
+#[cfg(feature = "tower")]
+#[test(tokio::test)]
+async fn tower_errors_are_classified() {
+    use axum::body::Body;
+    use axum_supabase_auth::api::TowerTransport;
+    use std::time::Duration;
+    use tower::timeout::Timeout;
+    use tower::{service_fn, BoxError};
+
+    // Arrange
+    let slow = Timeout::new(
+        service_fn(|_request| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, BoxError>(Response::new(Body::empty()))
+        }),
+        Duration::from_millis(10),
+    );
+    let refused = service_fn(|_request| async {
+        Err::<Response<Body>, _>(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+    });
+
+    // Act
+    let timeout = client(TowerTransport::new(slow)).health_check().await;
+    let connect = client(TowerTransport::new(refused)).health_check().await;
+
+    // Assert
+    assert_matches!(timeout, Err(ApiError::Transport(e)) if e.kind() == TransportErrorKind::Timeout);
+    assert_matches!(connect, Err(ApiError::Transport(e)) if e.kind() == TransportErrorKind::Connect);
+}